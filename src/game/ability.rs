@@ -0,0 +1,338 @@
+//! Optional movement abilities, given to characters by their [`Skin`](super::skin::Skin).
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use super::{
+    input::{ActionState, InputAction},
+    movement::{
+        apply_movement, compute_can_jump, update_jump_buffer, CanJump, Grounded, JumpBuffer,
+        JumpSettings, MovementController,
+    },
+};
+use crate::{screen::PlayingState, AppSet};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<DoubleJump>();
+    app.register_type::<WallJump>();
+    app.register_type::<Dash>();
+    app.register_type::<DoubleJumpEvent>();
+    app.register_type::<WallJumpEvent>();
+    app.register_type::<DashEvent>();
+    app.add_event::<DoubleJumpEvent>();
+    app.add_event::<WallJumpEvent>();
+    app.add_event::<DashEvent>();
+    app.add_systems(
+        Update,
+        record_dash_input
+            .in_set(AppSet::RecordInput)
+            .run_if(in_state(PlayingState::Gameplay)),
+    );
+    app.add_systems(
+        FixedUpdate,
+        (
+            (detect_walls, apply_double_jump)
+                .chain()
+                .after(compute_can_jump)
+                .after(update_jump_buffer)
+                .before(apply_movement),
+            (apply_wall_jump, apply_dash).chain().after(apply_movement),
+        )
+            .before(PhysicsSet::SyncBackend),
+    );
+}
+
+/// Jump again while in the air.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct DoubleJump {
+    /// How many jumps can be made before landing again.
+    pub charges: u32,
+    used: u32,
+}
+
+impl DoubleJump {
+    pub fn new(charges: u32) -> Self {
+        Self { charges, used: 0 }
+    }
+}
+
+/// Jump away from a wall while in the air.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct WallJump {
+    /// Horizontal speed away from the wall.
+    pub push: f32,
+    /// How many seconds horizontal input is ignored after a wall jump,
+    /// so the player doesn't run straight back into the wall.
+    pub lock: f32,
+    /// Which side the wall being touched is on: -1 for left, 1 for right.
+    wall: Option<f32>,
+    direction: f32,
+    locked: Timer,
+}
+
+impl WallJump {
+    pub fn new(push: f32, lock: f32) -> Self {
+        Self {
+            push,
+            lock,
+            wall: None,
+            direction: 0.0,
+            locked: Timer::default(),
+        }
+    }
+}
+
+/// A quick horizontal burst, ignoring gravity.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct Dash {
+    pub speed: f32,
+    /// How many seconds a dash lasts.
+    pub duration: f32,
+    /// How many seconds after a dash ends before the next one.
+    pub cooldown: f32,
+    requested: bool,
+    direction: f32,
+    active: Timer,
+    ready: Timer,
+}
+
+impl Dash {
+    pub fn new(speed: f32, duration: f32, cooldown: f32) -> Self {
+        Self {
+            speed,
+            duration,
+            cooldown,
+            requested: false,
+            direction: 1.0,
+            active: Timer::default(),
+            ready: Timer::default(),
+        }
+    }
+}
+
+/// Sent when an entity jumps in the air with [`DoubleJump`].
+#[derive(Event, Reflect)]
+pub struct DoubleJumpEvent(pub Entity);
+
+/// Sent when an entity jumps off a wall with [`WallJump`].
+#[derive(Event, Reflect)]
+pub struct WallJumpEvent(pub Entity);
+
+/// Sent when an entity starts a [`Dash`].
+#[derive(Event, Reflect)]
+pub struct DashEvent(pub Entity);
+
+fn record_dash_input(actions: Res<ActionState>, mut query: Query<&mut Dash>) {
+    if !actions.just_pressed(InputAction::Dash) {
+        return;
+    }
+    for mut dash in &mut query {
+        dash.requested = true;
+    }
+}
+
+/// Look for a wall on each side while in the air, the way [`movement`](super::movement) looks for the ground.
+fn detect_walls(
+    rapier_context: Res<RapierContext>,
+    mut query: Query<(Entity, &GlobalTransform, &Grounded, &mut WallJump)>,
+) {
+    for (entity, global_transform, grounded, mut wall_jump) in &mut query {
+        wall_jump.wall = None;
+        if grounded.on_ground() {
+            continue;
+        }
+        let options = ShapeCastOptions {
+            max_time_of_impact: 16.0,
+            target_distance: 0.0,
+            stop_at_penetration: true,
+            compute_impact_geometry_on_penetration: true,
+        };
+        let filter = QueryFilter {
+            exclude_rigid_body: Some(entity),
+            flags: QueryFilterFlags::EXCLUDE_SENSORS,
+            ..QueryFilter::default()
+        };
+        // A bit smaller than the player, so the ground below doesn't count as a wall.
+        let shape = Collider::ball(56f32);
+        for side in [-1.0, 1.0] {
+            if rapier_context
+                .cast_shape(
+                    global_transform.translation().xy(),
+                    Rot::default(),
+                    Vec2::X * side,
+                    &shape,
+                    options,
+                    filter,
+                )
+                .is_some()
+            {
+                wall_jump.wall = Some(side);
+            }
+        }
+    }
+}
+
+fn apply_double_jump(
+    mut query: Query<(
+        Entity,
+        &Grounded,
+        &CanJump,
+        &JumpSettings,
+        &mut JumpBuffer,
+        &mut DoubleJump,
+        &mut Velocity,
+        Option<&WallJump>,
+    )>,
+    mut double_jump_event: EventWriter<DoubleJumpEvent>,
+) {
+    for (
+        entity,
+        grounded,
+        can_jump,
+        settings,
+        mut buffer,
+        mut double_jump,
+        mut velocity,
+        wall_jump,
+    ) in &mut query
+    {
+        if grounded.on_ground() {
+            double_jump.used = 0;
+        }
+        // Leave jumps from the ground, or right after leaving it, to the regular jump,
+        // and jumps next to a wall to the wall jump.
+        if grounded.on_ground()
+            || can_jump.0
+            || wall_jump.is_some_and(|wall_jump| wall_jump.wall.is_some())
+        {
+            continue;
+        }
+        if buffer.pending() && double_jump.used < double_jump.charges {
+            buffer.consume();
+            double_jump.used += 1;
+            velocity.linvel.y = settings.velocity;
+            double_jump_event.send(DoubleJumpEvent(entity));
+        }
+    }
+}
+
+fn apply_wall_jump(
+    time: Res<Time>,
+    mut query: Query<(
+        Entity,
+        &CanJump,
+        &JumpSettings,
+        &mut JumpBuffer,
+        &mut WallJump,
+        &mut Velocity,
+    )>,
+    mut wall_jump_event: EventWriter<WallJumpEvent>,
+) {
+    for (entity, can_jump, settings, mut buffer, mut wall_jump, mut velocity) in &mut query {
+        wall_jump.locked.tick(time.delta());
+        if let Some(wall) = wall_jump.wall {
+            if buffer.pending() && !can_jump.0 {
+                buffer.consume();
+                wall_jump.direction = -wall;
+                wall_jump.locked = Timer::from_seconds(wall_jump.lock, TimerMode::Once);
+                velocity.linvel.y = settings.velocity;
+                wall_jump_event.send(WallJumpEvent(entity));
+            }
+        }
+        // Keep going away from the wall, whatever the movement controls say.
+        if !wall_jump.locked.finished() {
+            velocity.linvel.x = wall_jump.direction * wall_jump.push;
+        }
+    }
+}
+
+fn apply_dash(
+    time: Res<Time>,
+    mut query: Query<(
+        Entity,
+        &MovementController,
+        Option<&Sprite>,
+        &mut Dash,
+        &mut Velocity,
+        &mut GravityScale,
+    )>,
+    mut dash_event: EventWriter<DashEvent>,
+) {
+    for (entity, controller, sprite, mut dash, mut velocity, mut gravity) in &mut query {
+        dash.active.tick(time.delta());
+        dash.ready.tick(time.delta());
+        if std::mem::take(&mut dash.requested) && dash.ready.finished() {
+            // Dash where the player is going, or where they are facing when standing still.
+            dash.direction = if controller.0.x != 0.0 {
+                controller.0.x.signum()
+            } else if sprite.is_some_and(|sprite| sprite.flip_x) {
+                -1.0
+            } else {
+                1.0
+            };
+            dash.active = Timer::from_seconds(dash.duration, TimerMode::Once);
+            dash.ready = Timer::from_seconds(dash.duration + dash.cooldown, TimerMode::Once);
+            dash_event.send(DashEvent(entity));
+        }
+        if !dash.active.finished() {
+            velocity.linvel = Vec2::new(dash.direction * dash.speed, 0.0);
+            gravity.0 = 0.0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::game::movement::{self, CoyoteTime, GroundProbe, JumpDelay, Movement};
+
+    #[test]
+    fn jump_pressed_in_the_air_double_jumps_on_the_same_tick() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, movement::plugin, super::plugin));
+        // Nothing to collide with, so the probe never finds the ground.
+        app.insert_resource(RapierContext::default());
+        app.insert_resource(RapierConfiguration::new(1.0));
+
+        // Long enough in the air that coyote time is over.
+        let mut coyote_time = Timer::from_seconds(0.25, TimerMode::Once);
+        coyote_time.tick(Duration::from_secs(1));
+        let settings = JumpSettings::default();
+        let entity = app
+            .world_mut()
+            .spawn((
+                TransformBundle::default(),
+                MovementController::default(),
+                Movement { speed: 420.0 },
+                settings,
+                JumpBuffer::default(),
+                Velocity::zero(),
+                GravityScale(1.0),
+                JumpDelay(Timer::default()),
+                CoyoteTime(coyote_time),
+                CanJump(false),
+                GroundProbe::default(),
+                Grounded::default(),
+                DoubleJump::new(1),
+            ))
+            .id();
+
+        app.world_mut().run_schedule(FixedUpdate);
+        assert_eq!(app.world().get::<Velocity>(entity).unwrap().linvel.y, 0.0);
+
+        app.world_mut()
+            .get_mut::<MovementController>(entity)
+            .unwrap()
+            .0 = Vec2::Y;
+        app.world_mut().run_schedule(FixedUpdate);
+
+        let velocity = app.world().get::<Velocity>(entity).unwrap();
+        assert_eq!(velocity.linvel.y, settings.velocity);
+        assert_eq!(app.world().resource::<Events<DoubleJumpEvent>>().len(), 1);
+    }
+}
@@ -1,161 +1,171 @@
-//! Player sprite animation.
+//! Sprite animation, playing named clips described by [`AnimationSheet`] assets.
 //! This is based on multiple examples and may be very different for your game.
 //! - [Sprite flipping](https://github.com/bevyengine/bevy/blob/latest/examples/2d/sprite_flipping.rs)
 //! - [Sprite animation](https://github.com/bevyengine/bevy/blob/latest/examples/2d/sprite_animation.rs)
-//! - [Timers](https://github.com/bevyengine/bevy/blob/latest/examples/time/timers.rs)
-
-use std::time::Duration;
 
 use bevy::prelude::*;
 
-use super::{audio::sfx::PlaySfx, movement::MovementController};
+use bevy_rapier2d::dynamics::Velocity;
+
+use super::{
+    assets::sheet::{AnimationClip, AnimationSheet, LoopMode},
+    locomotion::{update_locomotion, LocomotionChanged, LocomotionState},
+};
 use crate::AppSet;
 
 pub(super) fn plugin(app: &mut App) {
     // Animate and play sound effects based on controls.
-    app.register_type::<PlayerAnimation>();
+    app.register_type::<SpriteAnimator>();
+    app.observe(animate_locomotion);
     app.add_systems(
         Update,
-        (
-            update_animation_timer.in_set(AppSet::TickTimers),
-            (
-                update_animation_movement,
-                update_animation_atlas,
-                trigger_step_sfx,
-            )
-                .chain()
-                .in_set(AppSet::Update),
-        ),
+        (update_sprite_direction, animate_sprites)
+            .chain()
+            .after(update_locomotion)
+            .in_set(AppSet::Update),
     );
 }
 
-/// Update the sprite direction and animation state (idling/walking).
-fn update_animation_movement(
-    mut player_query: Query<(&MovementController, &mut Sprite, &mut PlayerAnimation)>,
-) {
-    for (controller, mut sprite, mut animation) in &mut player_query {
-        let dx = controller.0.x;
-        if dx != 0.0 {
+/// Horizontal speed above which the sprite turns to face where it goes.
+const TURN_THRESHOLD: f32 = 1.0;
+
+/// Update the sprite direction.
+/// This follows the actual movement, so running into a wall or auto-running look right.
+fn update_sprite_direction(mut player_query: Query<(&Velocity, &mut Sprite)>) {
+    for (velocity, mut sprite) in &mut player_query {
+        let dx = velocity.linvel.x;
+        if dx.abs() > TURN_THRESHOLD {
             sprite.flip_x = dx < 0.0;
         }
-
-        let animation_state = if controller.0 == Vec2::ZERO {
-            PlayerAnimationState::Idling
-        } else {
-            PlayerAnimationState::Walking
-        };
-        animation.update_state(animation_state);
     }
 }
 
-/// Update the animation timer.
-fn update_animation_timer(time: Res<Time>, mut query: Query<&mut PlayerAnimation>) {
-    for mut animation in &mut query {
-        animation.update_timer(time.delta());
-    }
+/// Play the clip matching the new locomotion state.
+fn animate_locomotion(trigger: Trigger<LocomotionChanged>, mut query: Query<&mut SpriteAnimator>) {
+    let Ok(mut animator) = query.get_mut(trigger.entity()) else {
+        return;
+    };
+    animator.play(match trigger.event().to {
+        LocomotionState::Idle => "idle",
+        LocomotionState::Run => "run",
+        LocomotionState::JumpRise => "jump",
+        LocomotionState::Fall | LocomotionState::FastFall => "fall",
+        LocomotionState::Land => "land",
+    });
 }
 
-/// Update the texture atlas to reflect changes in the animation.
-fn update_animation_atlas(mut query: Query<(&PlayerAnimation, &mut TextureAtlas)>) {
-    for (animation, mut atlas) in &mut query {
-        if animation.changed() {
-            atlas.index = animation.get_atlas_index();
+/// Advance animations, show their current frame, and trigger the events of the frames shown.
+fn animate_sprites(
+    mut commands: Commands,
+    time: Res<Time>,
+    sheets: Res<Assets<AnimationSheet>>,
+    mut query: Query<(Entity, &mut SpriteAnimator, &mut TextureAtlas)>,
+    mut shown: Local<Vec<usize>>,
+) {
+    for (entity, mut animator, mut atlas) in &mut query {
+        let Some(sheet) = sheets.get(&animator.sheet) else {
+            continue;
+        };
+        if atlas.layout != sheet.layout {
+            atlas.layout = sheet.layout.clone();
+        }
+        let Some(clip) = sheet.clip(&animator.clip) else {
+            continue;
+        };
+        shown.clear();
+        if std::mem::take(&mut animator.started) {
+            shown.push(animator.frame);
+        }
+        animator.advance(clip, time.delta_seconds(), &mut shown);
+        let index = clip.first + animator.frame;
+        if atlas.index != index {
+            atlas.index = index;
         }
-    }
-}
 
-/// If the player is moving, play a step sound effect synchronized with the animation.
-fn trigger_step_sfx(mut commands: Commands, mut step_query: Query<&PlayerAnimation>) {
-    for animation in &mut step_query {
-        if animation.state == PlayerAnimationState::Walking
-            && animation.changed()
-            && (animation.frame == 2 || animation.frame == 5)
-        {
-            commands.trigger(PlaySfx::RandomStep);
+        for frame in shown.iter() {
+            for name in clip.events.get(frame).into_iter().flatten() {
+                commands.trigger_targets(AnimationEvent { name: name.clone() }, entity);
+            }
         }
     }
 }
 
-/// Component that tracks player's animation state.
-/// It is tightly bound to the texture atlas we use.
-#[derive(Component, Reflect)]
-#[reflect(Component)]
-pub struct PlayerAnimation {
-    timer: Timer,
-    frame: usize,
-    state: PlayerAnimationState,
+/// Triggered on an entity when its [`SpriteAnimator`] shows a frame with events.
+/// Observe it to react to animations, like playing footsteps.
+#[derive(Event, Debug, Clone)]
+pub struct AnimationEvent {
+    /// The event name, as written in the [`AnimationSheet`].
+    pub name: String,
 }
 
-#[derive(Reflect, PartialEq)]
-pub enum PlayerAnimationState {
-    Idling,
-    Walking,
+/// Plays the clips of an [`AnimationSheet`] on a sprite with a [`TextureAtlas`].
+/// The atlas layout is taken from the sheet, so it doesn't need to be set when spawning.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub struct SpriteAnimator {
+    pub sheet: Handle<AnimationSheet>,
+    clip: String,
+    /// Frame of the clip being shown, starting at 0.
+    frame: usize,
+    /// Seconds the current frame has been shown.
+    elapsed: f32,
+    /// Whether a ping-pong clip is playing backwards.
+    reversed: bool,
+    /// Whether the first frame hasn't been shown yet.
+    started: bool,
 }
 
-impl PlayerAnimation {
-    /// The number of idle frames.
-    const IDLE_FRAMES: usize = 2;
-    /// The duration of each idle frame.
-    const IDLE_INTERVAL: Duration = Duration::from_millis(500);
-
-    fn idling() -> Self {
+impl SpriteAnimator {
+    pub fn new(sheet: Handle<AnimationSheet>, clip: &str) -> Self {
         Self {
-            timer: Timer::new(Self::IDLE_INTERVAL, TimerMode::Repeating),
+            sheet,
+            clip: clip.to_string(),
             frame: 0,
-            state: PlayerAnimationState::Idling,
+            elapsed: 0.0,
+            reversed: false,
+            started: true,
         }
     }
 
-    /// The number of walking frames.
-    const WALKING_FRAMES: usize = 6;
-    /// The duration of each walking frame.
-    const WALKING_INTERVAL: Duration = Duration::from_millis(50);
-
-    fn walking() -> Self {
-        Self {
-            timer: Timer::new(Self::WALKING_INTERVAL, TimerMode::Repeating),
-            frame: 0,
-            state: PlayerAnimationState::Walking,
+    /// Play a clip from the start, unless it is already playing.
+    pub fn play(&mut self, clip: &str) {
+        if self.clip != clip {
+            *self = Self::new(self.sheet.clone(), clip);
         }
     }
 
-    pub fn new() -> Self {
-        Self::idling()
-    }
-
-    /// Update animation timers.
-    pub fn update_timer(&mut self, delta: Duration) {
-        self.timer.tick(delta);
-        if !self.timer.finished() {
-            return;
-        }
-        self.frame = (self.frame + 1)
-            % match self.state {
-                PlayerAnimationState::Idling => Self::IDLE_FRAMES,
-                PlayerAnimationState::Walking => Self::WALKING_FRAMES,
+    /// Move the clip forward by `delta` seconds, adding the frames shown to `shown`.
+    fn advance(&mut self, clip: &AnimationClip, delta: f32, shown: &mut Vec<usize>) {
+        self.elapsed += delta;
+        let last = clip.frame_count() - 1;
+        loop {
+            let duration = clip.duration(self.frame);
+            if duration <= 0.0 || self.elapsed < duration {
+                break;
+            }
+            self.elapsed -= duration;
+            let next = match clip.mode {
+                LoopMode::Loop if self.frame >= last => 0,
+                LoopMode::Loop => self.frame + 1,
+                LoopMode::Once => (self.frame + 1).min(last),
+                LoopMode::PingPong if last == 0 => 0,
+                LoopMode::PingPong => {
+                    if self.frame == 0 {
+                        self.reversed = false;
+                    } else if self.frame >= last {
+                        self.reversed = true;
+                    }
+                    if self.reversed {
+                        self.frame - 1
+                    } else {
+                        self.frame + 1
+                    }
+                }
             };
-    }
-
-    /// Update animation state if it changes.
-    pub fn update_state(&mut self, state: PlayerAnimationState) {
-        if self.state != state {
-            match state {
-                PlayerAnimationState::Idling => *self = Self::idling(),
-                PlayerAnimationState::Walking => *self = Self::walking(),
+            if next != self.frame {
+                self.frame = next;
+                shown.push(next);
             }
         }
     }
-
-    /// Whether animation changed this tick.
-    pub fn changed(&self) -> bool {
-        self.timer.finished()
-    }
-
-    /// Return sprite index in the atlas.
-    pub fn get_atlas_index(&self) -> usize {
-        match self.state {
-            PlayerAnimationState::Idling => self.frame,
-            PlayerAnimationState::Walking => 6 + self.frame,
-        }
-    }
 }
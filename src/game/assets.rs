@@ -3,8 +3,18 @@ use bevy::{
     render::texture::{ImageLoaderSettings, ImageSampler},
     utils::HashMap,
 };
+use serde::{Deserialize, Serialize};
+
+pub mod aseprite;
+pub mod level;
+pub mod sheet;
+
+use level::Level;
+use sheet::AnimationSheet;
 
 pub(super) fn plugin(app: &mut App) {
+    app.add_plugins((aseprite::plugin, level::plugin, sheet::plugin));
+
     app.register_type::<HandleMap<ImageKey>>();
     app.init_resource::<HandleMap<ImageKey>>();
 
@@ -13,9 +23,15 @@ pub(super) fn plugin(app: &mut App) {
 
     app.register_type::<HandleMap<SoundtrackKey>>();
     app.init_resource::<HandleMap<SoundtrackKey>>();
+
+    app.register_type::<HandleMap<LevelKey>>();
+    app.init_resource::<HandleMap<LevelKey>>();
+
+    app.register_type::<HandleMap<SheetKey>>();
+    app.init_resource::<HandleMap<SheetKey>>();
 }
 
-#[derive(Copy, Clone, Default, Debug, Eq, PartialEq, Hash, Reflect)]
+#[derive(Copy, Clone, Default, Debug, Eq, PartialEq, Hash, Reflect, Serialize, Deserialize)]
 pub enum ImageKey {
     #[default]
     Ducky,
@@ -103,7 +119,7 @@ impl FromWorld for HandleMap<ImageKey> {
     }
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Reflect)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Reflect, Serialize, Deserialize)]
 pub enum SfxKey {
     ButtonHover,
     ButtonPress,
@@ -111,6 +127,10 @@ pub enum SfxKey {
     Step2,
     Step3,
     Step4,
+    /// Played as dialogue lines are revealed.
+    /// It reuses the button hover sound on purpose: the short, soft tick works as a blip,
+    /// and dialogue can get a sound of its own by changing only what this key loads.
+    DialogueBlip,
 }
 
 impl AssetKey for SfxKey {
@@ -133,6 +153,11 @@ impl FromWorld for HandleMap<SfxKey> {
             (SfxKey::Step2, asset_server.load("audio/sfx/step2.ogg")),
             (SfxKey::Step3, asset_server.load("audio/sfx/step3.ogg")),
             (SfxKey::Step4, asset_server.load("audio/sfx/step4.ogg")),
+            // The hover sound on purpose, see `SfxKey::DialogueBlip`.
+            (
+                SfxKey::DialogueBlip,
+                asset_server.load("audio/sfx/button_hover.ogg"),
+            ),
         ]
         .into()
     }
@@ -165,6 +190,86 @@ impl FromWorld for HandleMap<SoundtrackKey> {
     }
 }
 
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Reflect)]
+pub enum LevelKey {
+    BevyCycle,
+    BevyJam,
+    Endless,
+}
+
+impl LevelKey {
+    /// All levels, in the order they are meant to be played.
+    pub const ALL: [LevelKey; 3] = [LevelKey::BevyCycle, LevelKey::BevyJam, LevelKey::Endless];
+
+    fn position(self) -> usize {
+        Self::ALL.iter().position(|&key| key == self).unwrap()
+    }
+
+    pub fn next(self) -> Option<LevelKey> {
+        Self::ALL.get(self.position() + 1).copied()
+    }
+
+    pub fn previous(self) -> Option<LevelKey> {
+        self.position()
+            .checked_sub(1)
+            .map(|position| Self::ALL[position])
+    }
+}
+
+impl AssetKey for LevelKey {
+    type Asset = Level;
+}
+
+impl FromWorld for HandleMap<LevelKey> {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        [
+            (
+                LevelKey::BevyCycle,
+                asset_server.load("levels/bevy_cycle.level.ron"),
+            ),
+            (
+                LevelKey::BevyJam,
+                asset_server.load("levels/bevy_jam.level.ron"),
+            ),
+            (
+                LevelKey::Endless,
+                asset_server.load("levels/endless.level.ron"),
+            ),
+        ]
+        .into()
+    }
+}
+
+#[derive(Copy, Clone, Default, Debug, Eq, PartialEq, Hash, Reflect)]
+pub enum SheetKey {
+    /// The layout shared by the player and NPC skins.
+    #[default]
+    Character,
+    /// Written by hand in the Aseprite JSON export format,
+    /// slicing `images/job.png` with the same grid as `animations/character.sheet.ron`.
+    /// Exporting `images/job.aseprite` with its tags would replace it.
+    Job,
+}
+
+impl AssetKey for SheetKey {
+    type Asset = AnimationSheet;
+}
+
+impl FromWorld for HandleMap<SheetKey> {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        [
+            (
+                SheetKey::Character,
+                asset_server.load("animations/character.sheet.ron"),
+            ),
+            (SheetKey::Job, asset_server.load("images/job.aseprite.json")),
+        ]
+        .into()
+    }
+}
+
 pub trait AssetKey: Sized {
     type Asset: Asset;
 }
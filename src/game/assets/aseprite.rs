@@ -0,0 +1,194 @@
+//! Sprite sheets exported from Aseprite, loaded as an [`AnimationSheet`].
+//!
+//! Export with `File > Export Sprite Sheet`, using the `Array` JSON data option with tags,
+//! and name the data file `<name>.aseprite.json`.
+//! The sheet image, the atlas layout and the clips all come from the export,
+//! so animations can be changed in Aseprite without touching the code.
+//! Frame events are read from the user data of tags, written as `footstep@2 footstep@5`.
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    render::texture::{ImageLoaderSettings, ImageSampler},
+    utils::HashMap,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use super::sheet::{AnimationClip, AnimationSheet, LoopMode};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset_loader::<AsepriteSheetLoader>();
+}
+
+/// The parts of Aseprite's JSON sheet data that are used.
+#[derive(Debug, Deserialize)]
+struct AsepriteSheet {
+    frames: Vec<AsepriteFrame>,
+    meta: AsepriteMeta,
+}
+
+#[derive(Debug, Deserialize)]
+struct AsepriteFrame {
+    frame: AsepriteRect,
+    /// In milliseconds.
+    duration: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct AsepriteRect {
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct AsepriteSize {
+    w: u32,
+    h: u32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AsepriteMeta {
+    /// Path of the sheet image, relative to the data file.
+    image: String,
+    size: AsepriteSize,
+    #[serde(default)]
+    frame_tags: Vec<AsepriteTag>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AsepriteTag {
+    name: String,
+    from: usize,
+    to: usize,
+    direction: String,
+    /// How many times the tag plays, if not forever.
+    #[serde(default)]
+    repeat: Option<String>,
+    /// User data of the tag, listing its frame events.
+    #[serde(default)]
+    data: Option<String>,
+}
+
+impl AsepriteTag {
+    /// Frame events are written in the user data of the tag as `name@frame`,
+    /// separated by spaces, with frames counted from the start of the tag.
+    /// For example `footstep@2 footstep@5`.
+    fn events(&self) -> HashMap<usize, Vec<String>> {
+        let mut events: HashMap<usize, Vec<String>> = HashMap::default();
+        for event in self.data.iter().flat_map(|data| data.split_whitespace()) {
+            let parsed = event
+                .split_once('@')
+                .and_then(|(name, frame)| Some((name, frame.parse::<usize>().ok()?)));
+            match parsed {
+                Some((name, frame)) => events.entry(frame).or_default().push(name.to_string()),
+                None => warn!("Invalid event {event:?} in Aseprite tag {:?}.", self.name),
+            }
+        }
+        events
+    }
+
+    fn loop_mode(&self) -> LoopMode {
+        if self.repeat.is_some() {
+            // Playing a few times before stopping isn't supported, so this plays once.
+            return LoopMode::Once;
+        }
+        match self.direction.as_str() {
+            "forward" => LoopMode::Loop,
+            "pingpong" => LoopMode::PingPong,
+            direction => {
+                warn!(
+                    "Aseprite tag {:?} plays {direction}, which isn't supported. Playing it forward.",
+                    self.name
+                );
+                LoopMode::Loop
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct AsepriteSheetLoader;
+
+/// Possible errors that can be produced by [`AsepriteSheetLoader`].
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum AsepriteSheetLoaderError {
+    #[error("Could not load Aseprite sheet: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse Aseprite sheet: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Aseprite tag {0:?} uses frames that are not in the sheet")]
+    TagOutOfRange(String),
+}
+
+impl AssetLoader for AsepriteSheetLoader {
+    type Asset = AnimationSheet;
+    type Settings = ();
+    type Error = AsepriteSheetLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let sheet = serde_json::from_slice::<AsepriteSheet>(&bytes)?;
+
+        let mut layout =
+            TextureAtlasLayout::new_empty(UVec2::new(sheet.meta.size.w, sheet.meta.size.h));
+        for AsepriteFrame { frame, .. } in &sheet.frames {
+            layout.add_texture(URect::new(
+                frame.x,
+                frame.y,
+                frame.x + frame.w,
+                frame.y + frame.h,
+            ));
+        }
+
+        let mut clips = HashMap::default();
+        for tag in &sheet.meta.frame_tags {
+            let Some(frames) = sheet.frames.get(tag.from..=tag.to) else {
+                return Err(AsepriteSheetLoaderError::TagOutOfRange(tag.name.clone()));
+            };
+            let clip = AnimationClip {
+                first: tag.from,
+                last: tag.to,
+                durations: frames
+                    .iter()
+                    .map(|frame| frame.duration as f32 / 1000.0)
+                    .collect(),
+                mode: tag.loop_mode(),
+                events: tag.events(),
+            };
+            clips.insert(tag.name.clone(), clip);
+        }
+
+        let image_path = load_context
+            .path()
+            .parent()
+            .unwrap_or(std::path::Path::new(""))
+            .join(&sheet.meta.image);
+        let image = load_context
+            .loader()
+            .with_settings(|settings: &mut ImageLoaderSettings| {
+                settings.sampler = ImageSampler::nearest();
+            })
+            .load(image_path);
+
+        Ok(AnimationSheet {
+            image: Some(image),
+            layout: load_context.add_labeled_asset("layout".to_string(), layout),
+            clips,
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["aseprite.json"]
+    }
+}
@@ -0,0 +1,161 @@
+//! Level description files, loaded as a Bevy asset.
+//!
+//! A level is a RON file with a `.level.ron` extension living in `assets/levels/`.
+//! It describes everything [`SpawnLevel`](crate::game::spawn::level::SpawnLevel) needs,
+//! so levels can be edited without recompiling the game.
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::HashMap,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::game::{
+    cutscene::Cutscene,
+    generator::GeneratorSettings,
+    movement::RunMode,
+    spawn::npc::SpawnNpc,
+    tags::Tag,
+    trigger::{actions::TriggerAction, TriggerFilter, TriggerPolicy},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<Level>();
+    app.init_asset_loader::<LevelLoader>();
+}
+
+/// Everything that makes up a level.
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct Level {
+    /// Name shown in the level select screen.
+    pub name: String,
+    /// Static colliders the player walks on.
+    #[serde(default)]
+    pub ground: Vec<GroundSegment>,
+    /// Floating text in the world.
+    #[serde(default)]
+    pub signs: Vec<Sign>,
+    /// Non-playable characters, spawned through [`SpawnNpc`].
+    #[serde(default)]
+    pub npcs: Vec<SpawnNpc>,
+    /// Sensors reacting to the player walking into them.
+    #[serde(default)]
+    pub triggers: Vec<TriggerZone>,
+    /// Scripted sequences, played by name with
+    /// [`TriggerAction::PlayCutscene`].
+    #[serde(default)]
+    pub cutscenes: HashMap<String, Cutscene>,
+    /// Force a [`RunMode`] in this level, instead of the one chosen in the settings.
+    #[serde(default)]
+    pub run_mode: Option<RunMode>,
+    /// Endlessly extend the level with generated segments.
+    #[serde(default)]
+    pub generator: Option<GeneratorSettings>,
+    /// The player respawns at their last checkpoint when falling below this height.
+    #[serde(default = "Level::default_kill_height")]
+    pub kill_height: f32,
+}
+
+impl Level {
+    fn default_kill_height() -> f32 {
+        -1000.0
+    }
+}
+
+/// A cuboid collider, centered on `position`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct GroundSegment {
+    pub position: Vec2,
+    /// Half of the width and height of the segment.
+    pub half_size: Vec2,
+    #[serde(default)]
+    pub tags: Vec<Tag>,
+    /// See [`SurfaceMaterial`](crate::game::movement::SurfaceMaterial).
+    #[serde(default)]
+    pub material: Option<Tag>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Sign {
+    pub text: String,
+    pub position: Vec2,
+    #[serde(default = "Sign::default_font_size")]
+    pub font_size: f32,
+    #[serde(default)]
+    pub tags: Vec<Tag>,
+}
+
+impl Sign {
+    fn default_font_size() -> f32 {
+        30.0
+    }
+}
+
+/// A circular sensor and the reactions it triggers.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct TriggerZone {
+    pub name: String,
+    pub position: Vec2,
+    pub radius: f32,
+    /// Groups of the zone itself, so it can be despawned by a [`TriggerAction::Group`].
+    #[serde(default)]
+    pub tags: Vec<Tag>,
+    #[serde(default)]
+    pub policy: TriggerPolicy,
+    #[serde(default)]
+    pub filter: TriggerFilter,
+    /// Story flag that must be set before the zone fires.
+    #[serde(default)]
+    pub requires_flag: Option<String>,
+    /// What happens when the zone is entered, in order.
+    #[serde(default)]
+    pub actions: Vec<TriggerAction>,
+    /// What happens when the zone is left, in order.
+    #[serde(default)]
+    pub exit_actions: Vec<TriggerAction>,
+}
+
+/// What happens when the player reaches the end of a level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LevelEnd {
+    /// Go back to the level select screen.
+    GameOver,
+    /// Continue with the level following this one.
+    NextLevel,
+}
+
+#[derive(Default)]
+pub struct LevelLoader;
+
+/// Possible errors that can be produced by [`LevelLoader`].
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum LevelLoaderError {
+    #[error("Could not load level: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse level: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+impl AssetLoader for LevelLoader {
+    type Asset = Level;
+    type Settings = ();
+    type Error = LevelLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes::<Level>(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
+}
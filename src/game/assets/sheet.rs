@@ -0,0 +1,142 @@
+//! Animation sheet files, loaded as a Bevy asset.
+//!
+//! A sheet is a RON file with a `.sheet.ron` extension living in `assets/animations/`.
+//! It describes the grid of a sprite sheet and the named clips played by
+//! [`SpriteAnimator`](crate::game::animation::SpriteAnimator),
+//! so sheets with a different layout don't need code changes.
+//! Sheets can also be exported from Aseprite, see [`aseprite`](super::aseprite).
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::HashMap,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<AnimationSheet>();
+    app.init_asset_loader::<AnimationSheetLoader>();
+}
+
+/// The layout of a sprite sheet, and the animations it contains.
+#[derive(Asset, TypePath, Debug)]
+pub struct AnimationSheet {
+    /// The image the sheet was made for, if it comes with one.
+    /// Otherwise it is used with the images of skins sharing its layout.
+    pub image: Option<Handle<Image>>,
+    pub layout: Handle<TextureAtlasLayout>,
+    pub clips: HashMap<String, AnimationClip>,
+}
+
+impl AnimationSheet {
+    pub fn clip(&self, name: &str) -> Option<&AnimationClip> {
+        self.clips.get(name)
+    }
+}
+
+/// A named animation: a range of frames of the sheet.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AnimationClip {
+    /// Atlas index of the first frame.
+    pub first: usize,
+    /// Atlas index of the last frame, included.
+    pub last: usize,
+    /// How many seconds each frame is shown.
+    /// Frames past the end of the list use the last duration.
+    pub durations: Vec<f32>,
+    #[serde(default)]
+    pub mode: LoopMode,
+    /// Names of the [`AnimationEvent`](crate::game::animation::AnimationEvent)s
+    /// triggered when a frame is shown, by frame of the clip starting at 0.
+    #[serde(default)]
+    pub events: HashMap<usize, Vec<String>>,
+}
+
+impl AnimationClip {
+    pub fn frame_count(&self) -> usize {
+        self.last.saturating_sub(self.first) + 1
+    }
+
+    pub fn duration(&self, frame: usize) -> f32 {
+        self.durations
+            .get(frame)
+            .or(self.durations.last())
+            .copied()
+            .unwrap_or(0.1)
+    }
+}
+
+/// What happens when a clip reaches its last frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum LoopMode {
+    /// Start again from the first frame.
+    #[default]
+    Loop,
+    /// Stay on the last frame.
+    Once,
+    /// Play backwards to the first frame, then forwards again.
+    PingPong,
+}
+
+/// How an animation sheet is written in a `.sheet.ron` file.
+#[derive(Debug, Deserialize)]
+struct AnimationSheetFile {
+    /// Size of a frame, in pixels.
+    tile_size: UVec2,
+    columns: u32,
+    rows: u32,
+    /// Space between frames.
+    #[serde(default)]
+    padding: Option<UVec2>,
+    /// Space before the first frame.
+    #[serde(default)]
+    offset: Option<UVec2>,
+    clips: HashMap<String, AnimationClip>,
+}
+
+#[derive(Default)]
+pub struct AnimationSheetLoader;
+
+/// Possible errors that can be produced by [`AnimationSheetLoader`].
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum AnimationSheetLoaderError {
+    #[error("Could not load animation sheet: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse animation sheet: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+impl AssetLoader for AnimationSheetLoader {
+    type Asset = AnimationSheet;
+    type Settings = ();
+    type Error = AnimationSheetLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let file = ron::de::from_bytes::<AnimationSheetFile>(&bytes)?;
+        let layout = TextureAtlasLayout::from_grid(
+            file.tile_size,
+            file.columns,
+            file.rows,
+            file.padding,
+            file.offset,
+        );
+        Ok(AnimationSheet {
+            image: None,
+            layout: load_context.add_labeled_asset("layout".to_string(), layout),
+            clips: file.clips,
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["sheet.ron"]
+    }
+}
@@ -1,10 +1,19 @@
 use bevy::{audio::PlaybackMode, prelude::*};
 use rand::seq::SliceRandom;
 
-use crate::game::assets::{HandleMap, SfxKey};
+use crate::game::{
+    animation::AnimationEvent,
+    assets::{HandleMap, SfxKey},
+    locomotion::{LocomotionChanged, LocomotionState},
+    movement::Grounded,
+    spawn::player::Player,
+    tags::Tag,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.observe(play_sfx);
+    app.observe(play_locomotion_sfx);
+    app.observe(play_animation_sfx);
 }
 
 fn play_sfx(
@@ -14,7 +23,7 @@ fn play_sfx(
 ) {
     let sfx_key = match trigger.event() {
         PlaySfx::Key(key) => *key,
-        PlaySfx::RandomStep => random_step(),
+        PlaySfx::Step(material) => random_step(*material),
     };
     commands.spawn(AudioSourceBundle {
         source: sfx_handles[&sfx_key].clone_weak(),
@@ -29,12 +38,55 @@ fn play_sfx(
 #[derive(Event)]
 pub enum PlaySfx {
     Key(SfxKey),
-    RandomStep,
+    /// A random step on ground with this [`SurfaceMaterial`](crate::game::movement::SurfaceMaterial).
+    Step(Option<Tag>),
 }
 
-fn random_step() -> SfxKey {
-    [SfxKey::Step1, SfxKey::Step2, SfxKey::Step3, SfxKey::Step4]
-        .choose(&mut rand::thread_rng())
-        .copied()
-        .unwrap()
+fn random_step(material: Option<Tag>) -> SfxKey {
+    let steps: &[SfxKey] = match material.as_ref().map(Tag::as_str) {
+        Some("grass") => &[SfxKey::Step1, SfxKey::Step2],
+        Some("stone") => &[SfxKey::Step3, SfxKey::Step4],
+        _ => &[SfxKey::Step1, SfxKey::Step2, SfxKey::Step3, SfxKey::Step4],
+    };
+    *steps.choose(&mut rand::thread_rng()).unwrap()
+}
+
+/// Play a step when leaving the ground and when landing.
+fn play_locomotion_sfx(
+    trigger: Trigger<LocomotionChanged>,
+    mut commands: Commands,
+    q_grounded: Query<&Grounded>,
+) {
+    let LocomotionChanged { from, to } = *trigger.event();
+    let leaving_ground = to == LocomotionState::JumpRise && !from.airborne();
+    if leaving_ground || to == LocomotionState::Land {
+        commands.trigger(PlaySfx::Step(ground_material(
+            &q_grounded,
+            trigger.entity(),
+        )));
+    }
+}
+
+/// What `entity` is standing on, to pick its step sounds.
+fn ground_material(q_grounded: &Query<&Grounded>, entity: Entity) -> Option<Tag> {
+    q_grounded
+        .get(entity)
+        .ok()
+        .and_then(|grounded| grounded.material)
+}
+
+/// Play a step on `footstep` animation events of the player.
+/// Steps aren't spatial, so NPCs walking around stay quiet.
+fn play_animation_sfx(
+    trigger: Trigger<AnimationEvent>,
+    mut commands: Commands,
+    q_player: Query<(), With<Player>>,
+    q_grounded: Query<&Grounded>,
+) {
+    if trigger.event().name == "footstep" && q_player.contains(trigger.entity()) {
+        commands.trigger(PlaySfx::Step(ground_material(
+            &q_grounded,
+            trigger.entity(),
+        )));
+    }
 }
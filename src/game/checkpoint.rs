@@ -0,0 +1,97 @@
+//! Respawning the player when they fall out of the world.
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use super::{
+    assets::ImageKey,
+    movement::{CoyoteTime, InterpolatedPosition, JumpDelay},
+    skin::apply_skin,
+    spawn::player::Player,
+};
+use crate::{AppSet, MainCamera};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<RespawnPoint>();
+    app.register_type::<KillPlane>();
+    app.observe(respawn_player);
+    app.add_systems(Update, check_out_of_bounds.in_set(AppSet::Update));
+}
+
+/// Where the player respawns, and with which skin.
+/// Updated by [`TriggerAction::Checkpoint`](super::trigger::actions::TriggerAction::Checkpoint)
+/// and [`CutsceneStep::Checkpoint`](super::cutscene::CutsceneStep::Checkpoint).
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct RespawnPoint {
+    pub position: Vec2,
+    pub skin: ImageKey,
+}
+
+/// Entities going below this height are out of the world.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub struct KillPlane {
+    pub height: f32,
+}
+
+/// Trigger this event to move the player back to their [`RespawnPoint`].
+#[derive(Event, Debug)]
+pub struct RespawnPlayer;
+
+fn check_out_of_bounds(
+    mut commands: Commands,
+    q_kill_plane: Query<&KillPlane>,
+    q_player: Query<&Transform, With<Player>>,
+) {
+    let Ok(kill_plane) = q_kill_plane.get_single() else {
+        return;
+    };
+    for transform in &q_player {
+        if transform.translation.y < kill_plane.height {
+            commands.trigger(RespawnPlayer);
+        }
+    }
+}
+
+fn respawn_player(
+    _trigger: Trigger<RespawnPlayer>,
+    mut commands: Commands,
+    mut q_player: Query<
+        (
+            Entity,
+            &RespawnPoint,
+            &mut Transform,
+            &mut Velocity,
+            &mut InterpolatedPosition,
+            &mut CoyoteTime,
+            &mut JumpDelay,
+        ),
+        With<Player>,
+    >,
+    mut q_camera: Query<&mut Transform, (With<MainCamera>, Without<Player>)>,
+) {
+    for (
+        entity,
+        respawn_point,
+        mut transform,
+        mut velocity,
+        mut interpolated,
+        mut coyote_time,
+        mut jump_delay,
+    ) in &mut q_player
+    {
+        transform.translation = respawn_point.position.extend(transform.translation.z);
+        interpolated.teleport(transform.translation);
+        *velocity = Velocity::zero();
+        coyote_time.0.reset();
+        jump_delay.0.reset();
+        let skin = respawn_point.skin;
+        commands.add(move |world: &mut World| apply_skin(world, entity, skin));
+
+        // Don't wait for the camera to catch up, so the level around the checkpoint is streamed in.
+        for mut camera in &mut q_camera {
+            camera.translation.x = transform.translation.x;
+        }
+    }
+}
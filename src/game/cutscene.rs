@@ -0,0 +1,244 @@
+//! Scripted sequences of steps, authored in level files and started by triggers.
+//!
+//! While a cutscene holds control, the game is in [`PlayingState::Cutscene`]:
+//! player input is ignored and the camera stops following the player.
+
+use std::collections::VecDeque;
+
+use bevy::{prelude::*, utils::HashMap};
+use serde::Deserialize;
+
+use super::{
+    animation::SpriteAnimator,
+    assets::ImageKey,
+    checkpoint::RespawnPoint,
+    skin::{apply_skin, Skin},
+    spawn::{chunk::apply_group_op, player::Player},
+    tags::{GroupOp, Tag, TagIndex},
+    trigger::actions::spawn_floating_text,
+};
+use crate::{
+    screen::{PlayingState, Screen},
+    AppSet, MainCamera,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        run_cutscene
+            .in_set(AppSet::Update)
+            .run_if(resource_exists::<CutscenePlayer>),
+    );
+    app.add_systems(OnExit(Screen::Playing), stop_cutscene);
+}
+
+/// An ordered list of steps.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Cutscene(pub Vec<CutsceneStep>);
+
+#[derive(Debug, Clone, Deserialize)]
+pub enum CutsceneStep {
+    /// Take control away from the player.
+    LockInput,
+    /// Move the camera to `to` over `duration` seconds.
+    PanCamera {
+        to: Vec2,
+        duration: f32,
+    },
+    /// Walk the NPCs with this tag to `x`, and wait for them to get there.
+    WalkNpc {
+        tag: Tag,
+        x: f32,
+        speed: f32,
+    },
+    /// Show text at a position in the world, and wait while it is shown.
+    ShowText {
+        text: String,
+        position: Vec2,
+        duration: f32,
+    },
+    Wait(f32),
+    /// Change the skin of the player.
+    ChangeSkin(ImageKey),
+    /// Respawn the player where they are now, with the skin they have now,
+    /// after they fall out of the world.
+    Checkpoint,
+    /// Apply an operation to all entities with this tag.
+    Group(Tag, GroupOp),
+    /// Give control back to the player.
+    /// This happens anyway when the cutscene ends.
+    Release,
+}
+
+impl CutsceneStep {
+    /// Run when the step begins. Returns where the camera is.
+    fn start(&self, world: &mut World) -> Vec3 {
+        match self {
+            CutsceneStep::LockInput => {
+                world
+                    .resource_mut::<NextState<PlayingState>>()
+                    .set(PlayingState::Cutscene);
+            }
+            CutsceneStep::Release => {
+                world
+                    .resource_mut::<NextState<PlayingState>>()
+                    .set(PlayingState::Gameplay);
+            }
+            CutsceneStep::ShowText {
+                text,
+                position,
+                duration,
+            } => spawn_floating_text(world, text.clone(), *position, *duration),
+            CutsceneStep::ChangeSkin(key) => {
+                let players: Vec<Entity> = world
+                    .query_filtered::<Entity, With<Player>>()
+                    .iter(world)
+                    .collect();
+                for player in players {
+                    apply_skin(world, player, *key);
+                }
+            }
+            CutsceneStep::Checkpoint => {
+                for (transform, &Skin(skin), mut respawn_point) in world
+                    .query_filtered::<(&Transform, &Skin, &mut RespawnPoint), With<Player>>()
+                    .iter_mut(world)
+                {
+                    *respawn_point = RespawnPoint {
+                        position: transform.translation.xy(),
+                        skin,
+                    };
+                }
+            }
+            CutsceneStep::Group(tag, op) => apply_group_op(world, *tag, op.clone()),
+            CutsceneStep::PanCamera { .. }
+            | CutsceneStep::WalkNpc { .. }
+            | CutsceneStep::Wait(_) => {}
+        }
+        world
+            .query_filtered::<&Transform, With<MainCamera>>()
+            .get_single(world)
+            .map(|camera| camera.translation)
+            .unwrap_or_default()
+    }
+
+    /// Progress the step, `elapsed` seconds after it started.
+    /// Returns whether the step is done.
+    fn update(&self, world: &mut World, elapsed: f32, delta: f32, camera_start: Vec3) -> bool {
+        match self {
+            CutsceneStep::PanCamera { to, duration } => {
+                let t = if *duration > 0.0 {
+                    (elapsed / duration).min(1.0)
+                } else {
+                    1.0
+                };
+                let target = to.extend(camera_start.z);
+                for mut camera in world
+                    .query_filtered::<&mut Transform, With<MainCamera>>()
+                    .iter_mut(world)
+                {
+                    camera.translation = camera_start.lerp(target, t * t * (3.0 - 2.0 * t));
+                }
+                t >= 1.0
+            }
+            CutsceneStep::WalkNpc { tag, x, speed } => {
+                let mut arrived = true;
+                let entities = world.resource::<TagIndex>().entities(world, *tag);
+                for entity in entities {
+                    let mut entity = world.entity_mut(entity);
+                    if !entity.contains::<Sprite>() {
+                        continue;
+                    }
+                    let Some(mut transform) = entity.get_mut::<Transform>() else {
+                        continue;
+                    };
+                    let distance = x - transform.translation.x;
+                    let step = speed * delta;
+                    let clip = if distance.abs() <= step {
+                        transform.translation.x = *x;
+                        "idle"
+                    } else {
+                        transform.translation.x += step * distance.signum();
+                        arrived = false;
+                        "run"
+                    };
+                    if let Some(mut sprite) = entity.get_mut::<Sprite>() {
+                        sprite.flip_x = distance < 0.0;
+                    }
+                    if let Some(mut animator) = entity.get_mut::<SpriteAnimator>() {
+                        animator.play(clip);
+                    }
+                }
+                arrived
+            }
+            CutsceneStep::ShowText { duration, .. } | CutsceneStep::Wait(duration) => {
+                elapsed >= *duration
+            }
+            CutsceneStep::LockInput
+            | CutsceneStep::Release
+            | CutsceneStep::ChangeSkin(_)
+            | CutsceneStep::Checkpoint
+            | CutsceneStep::Group(..) => true,
+        }
+    }
+}
+
+/// The cutscenes of the level being played, by name.
+#[derive(Component, Debug, Default)]
+pub struct LevelCutscenes(pub HashMap<String, Cutscene>);
+
+/// The cutscene being played.
+#[derive(Resource, Debug)]
+pub struct CutscenePlayer {
+    steps: VecDeque<CutsceneStep>,
+    /// Time since the current step started, if it did.
+    elapsed: Option<f32>,
+    camera_start: Vec3,
+}
+
+/// Start playing a cutscene, unless one is already playing.
+pub fn play_cutscene(world: &mut World, cutscene: Cutscene) {
+    if world.contains_resource::<CutscenePlayer>() {
+        warn!("A cutscene is already playing.");
+        return;
+    }
+    world.insert_resource(CutscenePlayer {
+        steps: cutscene.0.into(),
+        elapsed: None,
+        camera_start: Vec3::ZERO,
+    });
+}
+
+fn run_cutscene(world: &mut World) {
+    let Some(mut player) = world.remove_resource::<CutscenePlayer>() else {
+        return;
+    };
+    let mut delta = world.resource::<Time>().delta_seconds();
+    // Run as many steps as possible this frame, so instant steps don't wait for the next one.
+    while let Some(step) = player.steps.front().cloned() {
+        // Only the step that was already running sees the time that passed since last frame.
+        let (elapsed, step_delta) = match player.elapsed {
+            Some(elapsed) => {
+                let step_delta = std::mem::take(&mut delta);
+                (elapsed + step_delta, step_delta)
+            }
+            None => {
+                player.camera_start = step.start(world);
+                (0.0, 0.0)
+            }
+        };
+        player.elapsed = Some(elapsed);
+        if !step.update(world, elapsed, step_delta, player.camera_start) {
+            world.insert_resource(player);
+            return;
+        }
+        player.steps.pop_front();
+        player.elapsed = None;
+    }
+    world
+        .resource_mut::<NextState<PlayingState>>()
+        .set(PlayingState::Gameplay);
+}
+
+fn stop_cutscene(mut commands: Commands) {
+    commands.remove_resource::<CutscenePlayer>();
+}
@@ -0,0 +1,231 @@
+//! Lines said by characters, shown in a speech bubble above them.
+//!
+//! A line is revealed one character at a time, with a blip for each character.
+//! Pressing [`InputAction::Interact`] reveals the rest of the line in the bubble closest
+//! to the player, then moves on to the next one, and closes the bubble after the last line.
+
+use std::collections::VecDeque;
+
+use bevy::{
+    prelude::*,
+    sprite::Anchor,
+    text::{Text2dBounds, TextLayoutInfo},
+};
+
+use super::{
+    assets::SfxKey,
+    audio::sfx::PlaySfx,
+    input::{action_just_pressed, InputAction},
+    spawn::player::Player,
+};
+use crate::{screen::PlayingState, AppSet};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<SpeechBubble>();
+    app.add_systems(
+        Update,
+        (
+            tick_speech_bubbles.in_set(AppSet::TickTimers),
+            (
+                advance_dialogue.run_if(
+                    in_state(PlayingState::Gameplay)
+                        .and_then(action_just_pressed(InputAction::Interact)),
+                ),
+                reveal_lines,
+            )
+                .chain()
+                .in_set(AppSet::Update),
+            fit_speech_bubbles
+                .in_set(AppSet::Update)
+                .after(reveal_lines),
+        ),
+    );
+}
+
+/// Seconds between two revealed characters.
+const CHARACTER_INTERVAL: f32 = 0.035;
+/// Height of the bottom of the bubble above the center of the speaker.
+const BUBBLE_OFFSET: f32 = 90.0;
+/// Space between the text and the edges of the bubble.
+const BUBBLE_PADDING: f32 = 12.0;
+/// Lines longer than this wrap.
+const BUBBLE_MAX_WIDTH: f32 = 420.0;
+const BUBBLE_BACKGROUND: Color = Color::srgba(0.95, 0.95, 0.95, 0.9);
+const BUBBLE_TEXT: Color = Color::srgb(0.1, 0.1, 0.1);
+
+/// A speech bubble, child of the character saying the lines.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub struct SpeechBubble {
+    /// The line being said first, then the ones after it.
+    lines: VecDeque<String>,
+    /// How many characters of the current line are shown.
+    revealed: usize,
+    timer: Timer,
+    /// The text entity inside the bubble.
+    text: Entity,
+}
+
+impl SpeechBubble {
+    fn line(&self) -> &str {
+        self.lines.front().map(String::as_str).unwrap_or_default()
+    }
+
+    fn line_revealed(&self) -> bool {
+        self.revealed >= self.line().chars().count()
+    }
+}
+
+/// Make `speaker` say `lines`, replacing what it was saying.
+pub fn start_dialogue(world: &mut World, speaker: Entity, lines: Vec<String>) {
+    if lines.is_empty() || world.get_entity(speaker).is_none() {
+        return;
+    }
+    stop_dialogue(world, speaker);
+
+    let text = world
+        .spawn((
+            Name::new("Speech text"),
+            Text2dBundle {
+                text: Text::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 26.0,
+                        color: BUBBLE_TEXT,
+                        ..default()
+                    },
+                ),
+                text_anchor: Anchor::BottomCenter,
+                text_2d_bounds: Text2dBounds {
+                    size: Vec2::new(BUBBLE_MAX_WIDTH, f32::INFINITY),
+                },
+                transform: Transform::from_xyz(0.0, BUBBLE_PADDING, 0.1),
+                ..default()
+            },
+        ))
+        .id();
+    let bubble = world
+        .spawn((
+            Name::new("Speech bubble"),
+            SpriteBundle {
+                sprite: Sprite {
+                    color: BUBBLE_BACKGROUND,
+                    custom_size: Some(Vec2::ZERO),
+                    anchor: Anchor::BottomCenter,
+                    ..default()
+                },
+                // In front of the characters.
+                transform: Transform::from_xyz(0.0, BUBBLE_OFFSET, 10.0),
+                ..default()
+            },
+            SpeechBubble {
+                lines: lines.into(),
+                revealed: 0,
+                timer: Timer::from_seconds(CHARACTER_INTERVAL, TimerMode::Repeating),
+                text,
+            },
+        ))
+        .add_child(text)
+        .id();
+    world.entity_mut(speaker).add_child(bubble);
+}
+
+/// Close the speech bubble of `speaker`, if it is saying something.
+pub fn stop_dialogue(world: &mut World, speaker: Entity) {
+    let Some(children) = world.get::<Children>(speaker) else {
+        return;
+    };
+    let bubbles: Vec<Entity> = children
+        .iter()
+        .copied()
+        .filter(|&child| world.get::<SpeechBubble>(child).is_some())
+        .collect();
+    for bubble in bubbles {
+        world.entity_mut(bubble).despawn_recursive();
+    }
+}
+
+fn tick_speech_bubbles(time: Res<Time>, mut query: Query<&mut SpeechBubble>) {
+    for mut bubble in &mut query {
+        bubble.timer.tick(time.delta());
+    }
+}
+
+fn reveal_lines(
+    mut commands: Commands,
+    mut q_bubbles: Query<&mut SpeechBubble>,
+    mut q_text: Query<&mut Text>,
+) {
+    for mut bubble in &mut q_bubbles {
+        let mut blip = false;
+        for _ in 0..bubble.timer.times_finished_this_tick() {
+            if bubble.line_revealed() {
+                break;
+            }
+            let revealed = bubble.line().chars().nth(bubble.revealed);
+            blip |= revealed.is_some_and(|c| !c.is_whitespace());
+            bubble.revealed += 1;
+        }
+        // At most one blip a frame, so they don't pile up on slow frames.
+        if blip {
+            commands.trigger(PlaySfx::Key(SfxKey::DialogueBlip));
+        }
+
+        let Ok(mut text) = q_text.get_mut(bubble.text) else {
+            continue;
+        };
+        let shown: String = bubble.line().chars().take(bubble.revealed).collect();
+        if text.sections[0].value != shown {
+            text.sections[0].value = shown;
+        }
+    }
+}
+
+/// Advance the bubble closest to the player, so characters talking further away keep their line.
+fn advance_dialogue(
+    mut commands: Commands,
+    q_player: Query<&GlobalTransform, With<Player>>,
+    mut q_bubbles: Query<(Entity, &GlobalTransform, &mut SpeechBubble)>,
+) {
+    let Ok(player) = q_player.get_single() else {
+        return;
+    };
+    let player = player.translation().xy();
+    let distance = |transform: &GlobalTransform| transform.translation().xy().distance(player);
+    let Some((entity, _, mut bubble)) = q_bubbles
+        .iter_mut()
+        .min_by(|(_, a, _), (_, b, _)| distance(a).total_cmp(&distance(b)))
+    else {
+        return;
+    };
+    if !bubble.line_revealed() {
+        bubble.revealed = bubble.line().chars().count();
+        return;
+    }
+    bubble.lines.pop_front();
+    bubble.revealed = 0;
+    bubble.timer.reset();
+    if bubble.lines.is_empty() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Size the background of bubbles to the text they show.
+fn fit_speech_bubbles(
+    mut q_bubbles: Query<(&SpeechBubble, &mut Sprite)>,
+    q_layout: Query<&TextLayoutInfo>,
+) {
+    for (bubble, mut sprite) in &mut q_bubbles {
+        let Ok(layout) = q_layout.get(bubble.text) else {
+            continue;
+        };
+        let size = if layout.logical_size == Vec2::ZERO {
+            Vec2::ZERO
+        } else {
+            layout.logical_size + 2.0 * BUBBLE_PADDING
+        };
+        if sprite.custom_size != Some(size) {
+            sprite.custom_size = Some(size);
+        }
+    }
+}
@@ -0,0 +1,319 @@
+//! Seeded procedural generation of level segments, for endless levels.
+//!
+//! The generator does not touch the ECS: it only emits [`Segment`]s described with the same
+//! building blocks as [`Level`](super::assets::level::Level) files.
+//! Given the same seed and [`DifficultyCurve`], it always emits the same segments,
+//! so runs can be shared and layouts can be checked without running the game.
+
+use bevy::prelude::*;
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::Deserialize;
+
+use super::{
+    assets::{
+        level::{GroundSegment, Sign, TriggerZone},
+        ImageKey,
+    },
+    npc::NpcBehavior,
+    spawn::npc::SpawnNpc,
+    tags::{GroupOp, Tag},
+    trigger::{actions::TriggerAction, TriggerPolicy},
+};
+
+/// Height of the top of the generated ground.
+const GROUND_TOP: f32 = 25.0;
+const GROUND_HALF_HEIGHT: f32 = 50.0;
+const NPC_HEIGHT: f32 = 96.0;
+const SIGN_HEIGHT: f32 = 224.0;
+
+/// How a level with a generator is extended while the player runs through it.
+#[derive(Debug, Clone, Deserialize)]
+pub struct GeneratorSettings {
+    /// Seed of the run. A random one is picked for each run if not set.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Where the first generated segment starts.
+    pub start: f32,
+    /// How far ahead of the player segments are generated.
+    #[serde(default = "GeneratorSettings::default_lookahead")]
+    pub lookahead: f32,
+    #[serde(default)]
+    pub difficulty: DifficultyCurve,
+}
+
+impl GeneratorSettings {
+    fn default_lookahead() -> f32 {
+        3000.0
+    }
+}
+
+/// How difficulty grows with the number of generated segments.
+/// Difficulty is a value between 0 (trivial) and 1 (hardest).
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct DifficultyCurve {
+    /// Difficulty of the first segment.
+    pub start: f32,
+    /// Difficulty added by each segment.
+    pub ramp: f32,
+    /// Difficulty never goes above this value.
+    pub max: f32,
+}
+
+impl Default for DifficultyCurve {
+    fn default() -> Self {
+        Self {
+            start: 0.0,
+            ramp: 0.05,
+            max: 1.0,
+        }
+    }
+}
+
+impl DifficultyCurve {
+    pub fn at(&self, segment: u32) -> f32 {
+        (self.start + self.ramp * segment as f32)
+            .min(self.max)
+            .clamp(0.0, 1.0)
+    }
+}
+
+/// A generated piece of level, starting at `start` along the x axis.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Segment {
+    pub index: u32,
+    pub start: f32,
+    pub ground: Vec<GroundSegment>,
+    pub signs: Vec<Sign>,
+    pub npcs: Vec<SpawnNpc>,
+    pub triggers: Vec<TriggerZone>,
+}
+
+/// What the main feature of a segment is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encounter {
+    /// An NPC talking about bevy, and a skin trigger behind them.
+    Npc,
+    /// A raised platform to jump over.
+    Platform,
+    /// Nothing but ground.
+    Flat,
+}
+
+/// Emits an endless stream of [`Segment`]s.
+#[derive(Component, Debug, Clone)]
+pub struct LevelGenerator {
+    rng: ChaCha8Rng,
+    difficulty: DifficultyCurve,
+    next_index: u32,
+    /// Where the next segment starts.
+    cursor: f32,
+}
+
+impl LevelGenerator {
+    pub fn new(seed: u64, difficulty: DifficultyCurve, start: f32) -> Self {
+        Self {
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            difficulty,
+            next_index: 0,
+            cursor: start,
+        }
+    }
+
+    /// Where the next generated segment will start.
+    pub fn cursor(&self) -> f32 {
+        self.cursor
+    }
+
+    pub fn next_segment(&mut self) -> Segment {
+        let index = self.next_index;
+        let difficulty = self.difficulty.at(index);
+        let start = self.cursor;
+        let length = self.rng.gen_range(1200.0..2000.0);
+        let gap = if self.rng.gen::<f32>() < difficulty * 0.8 {
+            self.rng.gen_range(80.0..=80.0 + 240.0 * difficulty)
+        } else {
+            0.0
+        };
+        let mut segment = Segment {
+            index,
+            start,
+            ..default()
+        };
+        segment.ground.push(ground_between(start, start + length));
+
+        // Every tenth segment is a milestone completing the bevy cycle.
+        let encounter = if index % 10 == 9 {
+            self.add_milestone(&mut segment);
+            Encounter::Flat
+        } else {
+            let platform_chance = 0.2 + 0.3 * difficulty;
+            let roll = self.rng.gen::<f32>();
+            if roll < 0.5 {
+                Encounter::Npc
+            } else if roll < 0.5 + platform_chance {
+                Encounter::Platform
+            } else {
+                Encounter::Flat
+            }
+        };
+        match encounter {
+            Encounter::Npc => self.add_npc(&mut segment, start + length * 0.4),
+            Encounter::Platform => self.add_platform(&mut segment, difficulty, start, length),
+            Encounter::Flat => {}
+        }
+
+        self.next_index += 1;
+        self.cursor = start + length + gap;
+        segment
+    }
+
+    fn add_npc(&mut self, segment: &mut Segment, x: f32) {
+        let (image_key, skin, lines): (ImageKey, ImageKey, &[&str]) = *[
+            (
+                ImageKey::Job,
+                ImageKey::Job,
+                &[
+                    "Hey you look capable! What about getting a job?",
+                    "Wow your contributions are great! Let's make business together!",
+                    "Please help our company scale with Bevy!",
+                ][..],
+            ),
+            (
+                ImageKey::Dev,
+                ImageKey::Dev,
+                &[
+                    "A bevy user is a bevy developer who doesn't know it yet.",
+                    "There's so many areas to bevy, let's make it even better!",
+                    "SME is for Subject Matter Experts, working with them is great!",
+                ][..],
+            ),
+            (
+                ImageKey::Mockersf,
+                ImageKey::Bavy,
+                &["Have you heard of Bevy ?"][..],
+            ),
+            (
+                ImageKey::Joshua,
+                ImageKey::Bavy,
+                &["Have you heard of Bevy ?"][..],
+            ),
+        ]
+        .choose(&mut self.rng)
+        .unwrap();
+        let text = lines.choose(&mut self.rng).unwrap();
+        let behavior = match image_key {
+            ImageKey::Dev => NpcBehavior::Patrol {
+                waypoints: vec![x - 120.0, x + 120.0],
+                speed: 80.0,
+            },
+            ImageKey::Job => NpcBehavior::FacePlayer { radius: 800.0 },
+            _ => NpcBehavior::Wave { radius: 600.0 },
+        };
+        // The NPC and its trigger leave together once the player got the skin.
+        let encounter = Tag::new(&format!("encounter {}", segment.index));
+
+        segment.npcs.push(SpawnNpc {
+            image_key,
+            position: Vec2::new(x, NPC_HEIGHT),
+            tags: vec![Tag::new("npc"), encounter],
+            behavior,
+        });
+        // The NPC speaks up before the player reaches it.
+        segment.triggers.push(TriggerZone {
+            name: format!("talk {encounter}"),
+            position: Vec2::new(x - 500.0, NPC_HEIGHT),
+            radius: 250.0,
+            tags: vec![encounter],
+            actions: vec![TriggerAction::Say {
+                speaker: Some(encounter),
+                lines: vec![text.to_string()],
+            }],
+            policy: TriggerPolicy::Once,
+            ..default()
+        });
+        segment.triggers.push(TriggerZone {
+            name: encounter.to_string(),
+            position: Vec2::new(x + 300.0, NPC_HEIGHT),
+            radius: 320.0,
+            tags: vec![encounter],
+            actions: vec![
+                TriggerAction::ApplySkin(skin),
+                TriggerAction::Checkpoint,
+                TriggerAction::Group(encounter, GroupOp::Despawn),
+            ],
+            ..default()
+        });
+    }
+
+    fn add_platform(&mut self, segment: &mut Segment, difficulty: f32, start: f32, length: f32) {
+        let width = self.rng.gen_range(200.0..400.0);
+        let height = self.rng.gen_range(80.0..=80.0 + 100.0 * difficulty);
+        let x = self.rng.gen_range(start + 400.0..start + length - 400.0);
+        segment.ground.push(GroundSegment {
+            position: Vec2::new(x, height),
+            half_size: Vec2::new(width / 2.0, 25.0),
+            tags: vec![Tag::new("platform")],
+            material: Some(Tag::new("stone")),
+        });
+    }
+
+    fn add_milestone(&mut self, segment: &mut Segment) {
+        let x = segment.start + 400.0;
+        segment.signs.push(Sign {
+            text: "Yeah that was the bevy cycle".to_string(),
+            position: Vec2::new(x - 120.0, SIGN_HEIGHT),
+            font_size: 30.0,
+            tags: vec![],
+        });
+        segment.triggers.push(TriggerZone {
+            name: format!("endless milestone {}", segment.index),
+            position: Vec2::new(x + 300.0, NPC_HEIGHT),
+            radius: 320.0,
+            actions: vec![
+                TriggerAction::ApplySkin(ImageKey::SuperBevy),
+                TriggerAction::Checkpoint,
+            ],
+            policy: TriggerPolicy::Once,
+            ..default()
+        });
+    }
+}
+
+impl Iterator for LevelGenerator {
+    type Item = Segment;
+
+    fn next(&mut self) -> Option<Segment> {
+        Some(self.next_segment())
+    }
+}
+
+fn ground_between(start: f32, end: f32) -> GroundSegment {
+    GroundSegment {
+        position: Vec2::new((start + end) / 2.0, GROUND_TOP - GROUND_HALF_HEIGHT),
+        half_size: Vec2::new((end - start) / 2.0, GROUND_HALF_HEIGHT),
+        tags: vec![],
+        material: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segments(seed: u64, count: usize) -> Vec<Segment> {
+        LevelGenerator::new(seed, DifficultyCurve::default(), 1000.0)
+            .take(count)
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_gives_same_segments() {
+        assert_eq!(segments(42, 50), segments(42, 50));
+    }
+
+    #[test]
+    fn different_seeds_give_different_segments() {
+        assert_ne!(segments(42, 50), segments(43, 50));
+    }
+}
@@ -0,0 +1,408 @@
+//! Map raw input to game actions, so systems don't care which keys are pressed.
+//!
+//! Bindings are read from a config file in the platform config directory,
+//! so they can be changed without rebuilding the game.
+
+use bevy::{
+    input::InputSystem,
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use serde::{Deserialize, Serialize};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<ActionState>();
+    app.add_systems(Startup, load_input_bindings);
+    app.add_systems(
+        PreUpdate,
+        record_actions
+            .after(InputSystem)
+            .run_if(resource_exists::<InputBindings>),
+    );
+    app.add_systems(
+        Update,
+        save_input_bindings.run_if(resource_exists::<InputBindings>),
+    );
+}
+
+/// Where bindings are read from, like `~/.config/bevy_meta/input.ron` on Linux.
+/// Web builds don't have a file system and always use the default bindings.
+#[cfg(not(target_family = "wasm"))]
+fn config_path() -> Option<std::path::PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("bevy_meta").join("input.ron"))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum InputAction {
+    MoveLeft,
+    MoveRight,
+    Jump,
+    FastFall,
+    Dash,
+    Pause,
+    Interact,
+    /// Move the menu focus to the previous button.
+    MenuUp,
+    /// Move the menu focus to the next button.
+    MenuDown,
+    /// Press the focused menu button.
+    MenuConfirm,
+}
+
+/// Something the player can press.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    /// A button on any connected gamepad.
+    GamepadButton(GamepadButtonType),
+    /// Pushing an analog stick or trigger of any connected gamepad in a direction.
+    GamepadAxis(GamepadAxisType, AxisDirection),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AxisDirection {
+    Positive,
+    Negative,
+}
+
+/// Which bindings trigger each action.
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+pub struct InputBindings {
+    pub actions: HashMap<InputAction, Vec<Binding>>,
+    /// Analog input below this value is ignored.
+    #[serde(default = "InputBindings::default_dead_zone")]
+    pub dead_zone: f32,
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        use AxisDirection::{Negative, Positive};
+        use Binding::{GamepadAxis, GamepadButton, Key};
+        use GamepadAxisType::{LeftStickX, LeftStickY};
+        let actions = [
+            (
+                InputAction::MoveLeft,
+                vec![
+                    Key(KeyCode::KeyA),
+                    Key(KeyCode::ArrowLeft),
+                    GamepadButton(GamepadButtonType::DPadLeft),
+                    GamepadAxis(LeftStickX, Negative),
+                ],
+            ),
+            (
+                InputAction::MoveRight,
+                vec![
+                    Key(KeyCode::KeyD),
+                    Key(KeyCode::ArrowRight),
+                    GamepadButton(GamepadButtonType::DPadRight),
+                    GamepadAxis(LeftStickX, Positive),
+                ],
+            ),
+            (
+                InputAction::Jump,
+                vec![
+                    Key(KeyCode::KeyW),
+                    Key(KeyCode::ArrowUp),
+                    Key(KeyCode::Space),
+                    GamepadButton(GamepadButtonType::South),
+                ],
+            ),
+            (
+                InputAction::FastFall,
+                vec![
+                    Key(KeyCode::KeyS),
+                    Key(KeyCode::ArrowDown),
+                    GamepadButton(GamepadButtonType::DPadDown),
+                    GamepadAxis(LeftStickY, Negative),
+                ],
+            ),
+            (
+                InputAction::Dash,
+                vec![
+                    Key(KeyCode::ShiftLeft),
+                    Key(KeyCode::ShiftRight),
+                    GamepadButton(GamepadButtonType::East),
+                    GamepadButton(GamepadButtonType::RightTrigger),
+                ],
+            ),
+            (
+                InputAction::Pause,
+                vec![
+                    Key(KeyCode::Escape),
+                    GamepadButton(GamepadButtonType::Start),
+                ],
+            ),
+            (
+                InputAction::Interact,
+                vec![
+                    Key(KeyCode::KeyE),
+                    Key(KeyCode::Enter),
+                    GamepadButton(GamepadButtonType::West),
+                ],
+            ),
+            (
+                InputAction::MenuUp,
+                vec![
+                    Key(KeyCode::ArrowUp),
+                    GamepadButton(GamepadButtonType::DPadUp),
+                    GamepadAxis(LeftStickY, Positive),
+                ],
+            ),
+            (
+                InputAction::MenuDown,
+                vec![
+                    Key(KeyCode::ArrowDown),
+                    Key(KeyCode::Tab),
+                    GamepadButton(GamepadButtonType::DPadDown),
+                    GamepadAxis(LeftStickY, Negative),
+                ],
+            ),
+            (
+                InputAction::MenuConfirm,
+                vec![
+                    Key(KeyCode::Enter),
+                    Key(KeyCode::Space),
+                    GamepadButton(GamepadButtonType::South),
+                ],
+            ),
+        ];
+        Self {
+            actions: actions.into_iter().collect(),
+            dead_zone: Self::default_dead_zone(),
+        }
+    }
+}
+
+impl InputBindings {
+    fn default_dead_zone() -> f32 {
+        0.25
+    }
+
+    /// Read the bindings from the config file.
+    /// If there is none yet, it is created with the default bindings so it's easy to edit.
+    /// Actions missing from the file, like ones added since it was written, use their default bindings.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn load() -> Self {
+        let Some(path) = config_path() else {
+            return Self::default();
+        };
+        let config = match std::fs::read_to_string(&path) {
+            Ok(config) => config,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                let bindings = Self::default();
+                bindings.save();
+                return bindings;
+            }
+            Err(error) => {
+                warn!("Could not read input config {path:?}, using the default bindings: {error}");
+                return Self::default();
+            }
+        };
+        let mut bindings: Self = ron::from_str(&config).unwrap_or_else(|error| {
+            warn!("Invalid input config {path:?}, using the default bindings: {error}");
+            Self::default()
+        });
+        for (action, defaults) in Self::default().actions {
+            bindings.actions.entry(action).or_insert(defaults);
+        }
+        bindings
+    }
+
+    #[cfg(target_family = "wasm")]
+    pub fn load() -> Self {
+        Self::default()
+    }
+
+    /// Write the bindings to the config file.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn save(&self) {
+        let Some(path) = config_path() else {
+            return;
+        };
+        let config = match ron::ser::to_string_pretty(self, default()) {
+            Ok(config) => config,
+            Err(error) => {
+                warn!("Could not serialize input config: {error}");
+                return;
+            }
+        };
+        let written = match path.parent() {
+            Some(dir) => std::fs::create_dir_all(dir),
+            None => Ok(()),
+        }
+        .and_then(|()| std::fs::write(&path, config));
+        if let Err(error) = written {
+            warn!("Could not write input config {path:?}: {error}");
+        }
+    }
+
+    #[cfg(target_family = "wasm")]
+    pub fn save(&self) {}
+}
+
+fn load_input_bindings(mut commands: Commands) {
+    commands.insert_resource(InputBindings::load());
+}
+
+/// Write the bindings back to the config file when they are changed while the game runs.
+fn save_input_bindings(bindings: Res<InputBindings>) {
+    if bindings.is_changed() && !bindings.is_added() {
+        bindings.save();
+    }
+}
+
+/// How much each action is held, and which were pressed this frame.
+#[derive(Resource, Debug, Default)]
+pub struct ActionState {
+    /// Between 0 and 1. Buttons are either 0 or 1, analog input can be anything in between.
+    values: HashMap<InputAction, f32>,
+    just_pressed: HashSet<InputAction>,
+}
+
+impl ActionState {
+    pub fn value(&self, action: InputAction) -> f32 {
+        self.values.get(&action).copied().unwrap_or_default()
+    }
+
+    pub fn pressed(&self, action: InputAction) -> bool {
+        self.value(action) > 0.0
+    }
+
+    pub fn just_pressed(&self, action: InputAction) -> bool {
+        self.just_pressed.contains(&action)
+    }
+}
+
+/// Run condition that is true when `action` was pressed this frame.
+pub fn action_just_pressed(action: InputAction) -> impl Fn(Res<ActionState>) -> bool + Clone {
+    move |state: Res<ActionState>| state.just_pressed(action)
+}
+
+pub fn record_actions(
+    bindings: Res<InputBindings>,
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    mut state: ResMut<ActionState>,
+) {
+    let button_value = |pressed: bool| if pressed { 1.0 } else { 0.0 };
+    let binding_value = |binding: &Binding| match *binding {
+        Binding::Key(key) => button_value(keys.pressed(key)),
+        Binding::GamepadButton(button_type) => button_value(
+            gamepads
+                .iter()
+                .any(|gamepad| gamepad_buttons.pressed(GamepadButton::new(gamepad, button_type))),
+        ),
+        Binding::GamepadAxis(axis_type, direction) => gamepads
+            .iter()
+            .filter_map(|gamepad| gamepad_axes.get(GamepadAxis::new(gamepad, axis_type)))
+            .map(|value| {
+                let value = match direction {
+                    AxisDirection::Positive => value,
+                    AxisDirection::Negative => -value,
+                };
+                // Rescale what is past the dead zone, so the value still goes from 0 to 1.
+                ((value - bindings.dead_zone) / (1.0 - bindings.dead_zone)).clamp(0.0, 1.0)
+            })
+            .fold(0.0, f32::max),
+    };
+
+    let mut values = HashMap::default();
+    for (&action, action_bindings) in &bindings.actions {
+        let value = action_bindings
+            .iter()
+            .map(binding_value)
+            .fold(0.0, f32::max);
+        if value > 0.0 {
+            values.insert(action, value);
+        }
+    }
+    // Compare with the previous frame rather than asking each input,
+    // so analog input is pressed when it leaves the dead zone.
+    state.just_pressed = values
+        .keys()
+        .copied()
+        .filter(|action| !state.values.contains_key(action))
+        .collect();
+    state.values = values;
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::input::{
+        gamepad::{
+            GamepadAxisChangedEvent, GamepadButtonChangedEvent, GamepadConnection,
+            GamepadConnectionEvent, GamepadInfo,
+        },
+        InputPlugin,
+    };
+
+    use super::*;
+
+    const GAMEPAD: Gamepad = Gamepad { id: 0 };
+
+    /// An app recording actions from the default bindings, with a gamepad connected.
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins(InputPlugin);
+        app.insert_resource(InputBindings::default());
+        app.init_resource::<ActionState>();
+        app.add_systems(PreUpdate, record_actions.after(InputSystem));
+        app.world_mut().send_event(GamepadConnectionEvent::new(
+            GAMEPAD,
+            GamepadConnection::Connected(GamepadInfo {
+                name: "Test gamepad".to_string(),
+            }),
+        ));
+        app.update();
+        app
+    }
+
+    fn actions(app: &App) -> &ActionState {
+        app.world().resource::<ActionState>()
+    }
+
+    #[test]
+    fn gamepad_button_presses_action() {
+        let mut app = app();
+        app.world_mut().send_event(GamepadButtonChangedEvent::new(
+            GAMEPAD,
+            GamepadButtonType::South,
+            1.0,
+        ));
+        app.update();
+        assert!(actions(&app).pressed(InputAction::Jump));
+        assert!(actions(&app).just_pressed(InputAction::Jump));
+
+        app.update();
+        assert!(actions(&app).pressed(InputAction::Jump));
+        assert!(!actions(&app).just_pressed(InputAction::Jump));
+    }
+
+    #[test]
+    fn gamepad_axis_is_rescaled_past_the_dead_zone() {
+        let mut app = app();
+        let dead_zone = app.world().resource::<InputBindings>().dead_zone;
+
+        let inside = dead_zone * 0.5;
+        app.world_mut().send_event(GamepadAxisChangedEvent::new(
+            GAMEPAD,
+            GamepadAxisType::LeftStickX,
+            inside,
+        ));
+        app.update();
+        assert!(!actions(&app).pressed(InputAction::MoveRight));
+
+        let halfway = dead_zone + (1.0 - dead_zone) * 0.5;
+        app.world_mut().send_event(GamepadAxisChangedEvent::new(
+            GAMEPAD,
+            GamepadAxisType::LeftStickX,
+            halfway,
+        ));
+        app.update();
+        assert!(actions(&app).just_pressed(InputAction::MoveRight));
+        assert!((actions(&app).value(InputAction::MoveRight) - 0.5).abs() < 1e-5);
+        assert!(!actions(&app).pressed(InputAction::MoveLeft));
+    }
+}
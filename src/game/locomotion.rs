@@ -0,0 +1,128 @@
+//! What a character's body is doing, derived from physics and input.
+//!
+//! Animation and audio don't look at velocities themselves:
+//! they react to [`LocomotionChanged`], triggered on the character when its [`Locomotion`] changes.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_rapier2d::dynamics::Velocity;
+use serde::{Deserialize, Serialize};
+
+use super::movement::{Grounded, MovementController};
+use crate::AppSet;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Locomotion>();
+    app.add_systems(
+        Update,
+        (
+            tick_landing.in_set(AppSet::TickTimers),
+            update_locomotion.in_set(AppSet::Update),
+        ),
+    );
+}
+
+/// Horizontal speed above which a grounded character runs.
+const RUN_THRESHOLD: f32 = 1.0;
+/// Vertical speed above which a character is in the air, even if still touching the ground.
+const RISE_THRESHOLD: f32 = 50.0;
+/// How long landing lasts before running or idling again.
+const LANDING_DURATION: Duration = Duration::from_millis(120);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+pub enum LocomotionState {
+    #[default]
+    Idle,
+    Run,
+    JumpRise,
+    Fall,
+    /// Just touched the ground after being in the air.
+    Land,
+    FastFall,
+}
+
+impl LocomotionState {
+    pub fn airborne(self) -> bool {
+        matches!(
+            self,
+            LocomotionState::JumpRise | LocomotionState::Fall | LocomotionState::FastFall
+        )
+    }
+}
+
+/// The locomotion state machine of a character.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub struct Locomotion {
+    state: LocomotionState,
+    landing: Timer,
+}
+
+impl Default for Locomotion {
+    fn default() -> Self {
+        Self {
+            state: LocomotionState::Idle,
+            landing: Timer::new(LANDING_DURATION, TimerMode::Once),
+        }
+    }
+}
+
+impl Locomotion {
+    fn next_state(&self, grounded: bool, velocity: Vec2, controller: Vec2) -> LocomotionState {
+        if !grounded || velocity.y > RISE_THRESHOLD {
+            if velocity.y > 0.0 {
+                LocomotionState::JumpRise
+            } else if controller.y < -0.01 {
+                LocomotionState::FastFall
+            } else {
+                LocomotionState::Fall
+            }
+        } else if self.state.airborne()
+            || (self.state == LocomotionState::Land && !self.landing.finished())
+        {
+            LocomotionState::Land
+        } else if velocity.x.abs() > RUN_THRESHOLD {
+            LocomotionState::Run
+        } else {
+            LocomotionState::Idle
+        }
+    }
+}
+
+/// Triggered on a character when its [`Locomotion`] state changes.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct LocomotionChanged {
+    pub from: LocomotionState,
+    pub to: LocomotionState,
+}
+
+fn tick_landing(time: Res<Time>, mut query: Query<&mut Locomotion>) {
+    for mut locomotion in &mut query {
+        locomotion.landing.tick(time.delta());
+    }
+}
+
+pub(super) fn update_locomotion(
+    mut commands: Commands,
+    mut query: Query<(
+        Entity,
+        &Grounded,
+        &Velocity,
+        &MovementController,
+        &mut Locomotion,
+    )>,
+) {
+    for (entity, grounded, velocity, controller, mut locomotion) in &mut query {
+        let from = locomotion.state;
+        let to = locomotion.next_state(grounded.on_ground(), velocity.linvel, controller.0);
+        if from == to {
+            continue;
+        }
+        if to == LocomotionState::Land {
+            locomotion.landing.reset();
+        }
+        locomotion.state = to;
+        commands.trigger_targets(LocomotionChanged { from, to }, entity);
+    }
+}
@@ -6,22 +6,47 @@ use bevy_rapier2d::{
     render::RapierDebugRenderPlugin,
 };
 
-mod animation;
+pub mod ability;
+pub mod animation;
 pub mod assets;
 pub mod audio;
-mod movement;
+mod checkpoint;
+pub mod cutscene;
+pub mod dialogue;
+pub mod generator;
+pub mod input;
+pub mod locomotion;
+pub mod movement;
+pub mod npc;
+pub mod progress;
+pub mod skin;
 pub mod spawn;
+pub mod tags;
 pub mod trigger;
 
 pub(super) fn plugin(app: &mut App) {
     app.add_plugins((
-        RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0),
+        RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0).in_fixed_schedule(),
         RapierDebugRenderPlugin::default(),
+    ));
+    app.add_plugins((
         trigger::plugin,
+        ability::plugin,
         animation::plugin,
         audio::plugin,
+        checkpoint::plugin,
+        cutscene::plugin,
+        dialogue::plugin,
+    ));
+    app.add_plugins((
         assets::plugin,
+        input::plugin,
+        locomotion::plugin,
         movement::plugin,
+        npc::plugin,
+        progress::plugin,
+        skin::plugin,
         spawn::plugin,
+        tags::plugin,
     ));
 }
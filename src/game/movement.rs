@@ -1,51 +1,205 @@
 //! Handle player input and translate it into movement.
-//! Note that the approach used here is simple for demonstration purposes.
-//! If you want to move the player in a smoother way,
-//! consider using a [fixed timestep](https://github.com/bevyengine/bevy/blob/latest/examples/movement/physics_in_fixed_timestep.rs).
+//! Movement and physics run in a [fixed timestep](https://github.com/bevyengine/bevy/blob/latest/examples/movement/physics_in_fixed_timestep.rs),
+//! so they behave the same at any frame rate,
+//! and sprites are interpolated between ticks so they still move smoothly.
 
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
 
-use crate::AppSet;
+use super::{
+    input::{ActionState, InputAction},
+    tags::Tag,
+};
+use crate::{screen::PlayingState, AppSet};
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<MovementController>();
     app.register_type::<JumpEvent>();
-    app.register_type::<IsGrounded>();
+    app.register_type::<Grounded>();
     app.register_type::<JumpDelay>();
     app.register_type::<CoyoteTime>();
+    app.register_type::<JumpSettings>();
+    app.register_type::<JumpBuffer>();
     // Record directional input as movement controls.
     app.register_type::<MovementController>();
+    app.register_type::<RunMode>();
+    app.register_type::<RunModeSetting>();
+    app.init_resource::<RunModeSetting>();
+    app.register_type::<TickRate>();
+    app.init_resource::<TickRate>();
+    app.register_type::<InterpolatedPosition>();
     app.add_systems(Startup, set_gravity);
+    app.add_systems(Update, apply_tick_rate.run_if(resource_changed::<TickRate>));
     app.add_systems(
         Update,
-        record_movement_controller.in_set(AppSet::RecordInput),
+        record_movement_controller
+            .in_set(AppSet::RecordInput)
+            .run_if(in_state(PlayingState::Gameplay)),
     );
+    app.add_systems(OnEnter(PlayingState::Cutscene), stop_movement_controllers);
     app.add_event::<JumpEvent>();
     // Apply movement based on controls.
     app.register_type::<Movement>();
     app.add_systems(
-        Update,
+        FixedUpdate,
         (
             (
-                (compute_is_grounded, compute_can_jump).chain(),
+                (probe_ground, compute_can_jump).chain(),
+                update_jump_buffer,
                 apply_movement,
             )
                 .chain(),
             //wrap_within_window,
         )
             .chain()
-            .in_set(AppSet::Update),
+            .before(PhysicsSet::SyncBackend),
+    );
+    // Interpolate positions between physics ticks.
+    app.add_systems(FixedFirst, restore_physics_positions);
+    app.add_systems(
+        FixedUpdate,
+        record_physics_positions.after(PhysicsSet::Writeback),
     );
+    app.add_systems(
+        PostUpdate,
+        interpolate_transforms.before(TransformSystem::TransformPropagate),
+    );
+}
+
+/// How many times per second movement and physics are simulated.
+#[derive(Resource, Debug, Clone, Copy, Reflect)]
+#[reflect(Resource)]
+pub struct TickRate(pub f64);
+
+impl Default for TickRate {
+    fn default() -> Self {
+        Self(64.0)
+    }
+}
+
+fn apply_tick_rate(
+    tick_rate: Res<TickRate>,
+    mut time: ResMut<Time<Fixed>>,
+    mut conf: ResMut<RapierConfiguration>,
+) {
+    time.set_timestep_hz(tick_rate.0);
+    conf.timestep_mode = TimestepMode::Fixed {
+        dt: (1.0 / tick_rate.0) as f32,
+        substeps: 1,
+    };
+}
+
+/// Where a physics body was at the last two ticks.
+/// Its [`Transform`] is placed in between when rendering.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct InterpolatedPosition {
+    previous: Vec3,
+    current: Vec3,
+}
+
+impl InterpolatedPosition {
+    pub fn new(position: Vec3) -> Self {
+        Self {
+            previous: position,
+            current: position,
+        }
+    }
+
+    /// Move the body without interpolating from where it was.
+    pub fn teleport(&mut self, position: Vec3) {
+        *self = Self::new(position);
+    }
+}
+
+/// Put bodies back where physics left them, before the next tick.
+/// Rapier reads the [`GlobalTransform`] to detect bodies moved by hand,
+/// so it is restored too instead of waiting for transform propagation.
+fn restore_physics_positions(
+    mut query: Query<(&InterpolatedPosition, &mut Transform, &mut GlobalTransform)>,
+) {
+    for (position, mut transform, mut global_transform) in &mut query {
+        transform.translation = position.current;
+        *global_transform = GlobalTransform::from(*transform);
+    }
+}
+
+fn record_physics_positions(mut query: Query<(&mut InterpolatedPosition, &Transform)>) {
+    for (mut position, transform) in &mut query {
+        position.previous = position.current;
+        position.current = transform.translation;
+    }
+}
+
+pub(crate) fn interpolate_transforms(
+    time: Res<Time<Fixed>>,
+    mut query: Query<(&InterpolatedPosition, &mut Transform)>,
+) {
+    let t = time.overstep_fraction();
+    for (position, mut transform) in &mut query {
+        transform.translation = position.previous.lerp(position.current, t);
+    }
 }
 
 #[derive(Component, Reflect, Default)]
 #[reflect(Component)]
 pub struct MovementController(pub Vec2);
 
-#[derive(Component, Reflect, Default)]
+/// How an entity looks for the ground below it.
+#[derive(Component, Debug, Clone)]
+pub struct GroundProbe {
+    /// Shape cast down, usually about the size of the entity's collider.
+    pub shape: Collider,
+    /// How far below the entity the ground is looked for.
+    pub distance: f32,
+    /// Steepest slope that can be stood on, in radians.
+    pub max_slope: f32,
+}
+
+impl Default for GroundProbe {
+    fn default() -> Self {
+        Self {
+            shape: Collider::ball(64f32),
+            distance: 10.0,
+            max_slope: 45f32.to_radians(),
+        }
+    }
+}
+
+/// What an entity with a [`GroundProbe`] is standing on.
+#[derive(Component, Debug, Reflect)]
 #[reflect(Component)]
-pub struct IsGrounded(pub bool);
+pub struct Grounded {
+    /// The ground entity, if standing on anything.
+    pub entity: Option<Entity>,
+    /// Normal of the ground surface, pointing up when the ground is flat.
+    pub normal: Vec2,
+    /// The [`SurfaceMaterial`] of the ground, if it has one.
+    #[reflect(ignore)]
+    pub material: Option<Tag>,
+}
+
+impl Default for Grounded {
+    fn default() -> Self {
+        Self {
+            entity: None,
+            normal: Vec2::Y,
+            material: None,
+        }
+    }
+}
+
+impl Grounded {
+    pub fn on_ground(&self) -> bool {
+        self.entity.is_some()
+    }
+}
+
+/// What the surface of a ground entity is made of, for things like footstep sounds.
+/// `grass` and `stone` have their own steps, see [`PlaySfx::Step`](super::audio::sfx::PlaySfx::Step).
+#[derive(Component, Debug, Clone, Copy)]
+pub struct SurfaceMaterial(pub Tag);
 
 #[derive(Component, Reflect, Default)]
 #[reflect(Component)]
@@ -59,35 +213,134 @@ pub struct JumpDelay(pub Timer);
 #[reflect(Component)]
 pub struct CoyoteTime(pub Timer);
 
+/// How the jump of a character feels.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct JumpSettings {
+    /// Vertical velocity given when jumping.
+    pub velocity: f32,
+    /// How many seconds a jump pressed before it is possible is remembered.
+    pub buffer: f32,
+    /// Vertical velocity is multiplied by this when jump is released while rising,
+    /// so short presses make short jumps.
+    pub release_cut: f32,
+    /// Gravity scale while rising with jump held.
+    pub rising_gravity: f32,
+    /// Gravity scale the rest of the time.
+    pub falling_gravity: f32,
+    /// Gravity scale while fast falling.
+    pub fast_fall_gravity: f32,
+}
+
+impl Default for JumpSettings {
+    fn default() -> Self {
+        Self {
+            velocity: 600.0,
+            buffer: 0.12,
+            release_cut: 0.5,
+            rising_gravity: 0.5,
+            falling_gravity: 1.0,
+            fast_fall_gravity: 1.5,
+        }
+    }
+}
+
+/// Remembers a jump press for [`JumpSettings::buffer`] seconds,
+/// so a jump pressed just before landing isn't lost.
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+pub struct JumpBuffer {
+    timer: Timer,
+    /// Whether jump is held this tick.
+    held: bool,
+    /// Whether jump was let go this tick.
+    released: bool,
+}
+
+impl JumpBuffer {
+    /// Whether a jump was pressed recently and hasn't been used yet.
+    pub fn pending(&self) -> bool {
+        !self.timer.finished()
+    }
+
+    pub fn consume(&mut self) {
+        let remaining = self.timer.remaining();
+        self.timer.tick(remaining);
+    }
+}
+
 pub fn set_gravity(mut conf: ResMut<RapierConfiguration>) {
     conf.gravity = Vec2::new(0f32, -981f32 * 2f32);
 }
 
+/// How horizontal movement is controlled.
+#[derive(
+    Component, Debug, Clone, Copy, Default, PartialEq, Eq, Reflect, Serialize, Deserialize,
+)]
+#[reflect(Component)]
+pub enum RunMode {
+    /// Move left and right.
+    Free,
+    /// Only move right.
+    #[default]
+    RightOnly,
+    /// Always run right at full speed, input only controls jumping.
+    AutoRun,
+}
+
+impl RunMode {
+    pub const ALL: [RunMode; 3] = [RunMode::Free, RunMode::RightOnly, RunMode::AutoRun];
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|&mode| mode == self).unwrap();
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RunMode::Free => "Free",
+            RunMode::RightOnly => "Right only",
+            RunMode::AutoRun => "Auto-run",
+        }
+    }
+}
+
+/// The [`RunMode`] chosen in the settings, for levels that don't force one.
+#[derive(Resource, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub struct RunModeSetting(pub RunMode);
+
 fn record_movement_controller(
-    input: Res<ButtonInput<KeyCode>>,
-    mut controller_query: Query<&mut MovementController>,
+    actions: Res<ActionState>,
+    mut controller_query: Query<(&mut MovementController, &RunMode)>,
 ) {
     // Collect directional input.
     let mut intent = Vec2::ZERO;
-    if input.pressed(KeyCode::KeyW) || input.pressed(KeyCode::ArrowUp) {
+    if actions.pressed(InputAction::Jump) {
         intent.y += 1.0;
     }
-    if input.pressed(KeyCode::KeyS) || input.pressed(KeyCode::ArrowDown) {
-        intent.y -= 1.0;
-    }
-    if input.pressed(KeyCode::KeyA) || input.pressed(KeyCode::ArrowLeft) {
-        //intent.x -= 1.0;
-    }
-    if input.pressed(KeyCode::KeyD) || input.pressed(KeyCode::ArrowRight) {
-        intent.x += 1.0;
-    }
+    intent.y -= actions.value(InputAction::FastFall);
+    let right = actions.value(InputAction::MoveRight);
+    let left = actions.value(InputAction::MoveLeft);
 
     // Apply movement intent to controllers.
-    for mut controller in &mut controller_query {
+    for (mut controller, run_mode) in &mut controller_query {
+        intent.x = match run_mode {
+            RunMode::Free => right - left,
+            RunMode::RightOnly => right,
+            RunMode::AutoRun => 1.0,
+        };
         controller.0 = intent;
     }
 }
 
+/// Input is not recorded during cutscenes, so don't keep moving with the last one.
+fn stop_movement_controllers(mut controller_query: Query<&mut MovementController>) {
+    for mut controller in &mut controller_query {
+        controller.0 = Vec2::ZERO;
+    }
+}
+
 #[derive(Component, Reflect)]
 #[reflect(Component)]
 pub struct Movement {
@@ -101,13 +354,14 @@ pub struct Movement {
 #[derive(Event, Reflect)]
 pub struct JumpEvent(pub Entity);
 
-fn compute_is_grounded(
+fn probe_ground(
     rapier_context: Res<RapierContext>,
-    mut query: Query<(Entity, &GlobalTransform, &mut IsGrounded)>,
+    q_material: Query<&SurfaceMaterial>,
+    mut query: Query<(Entity, &GlobalTransform, &GroundProbe, &mut Grounded)>,
 ) {
-    for (entity, global_transform, mut is_grounded) in query.iter_mut() {
+    for (entity, global_transform, probe, mut grounded) in query.iter_mut() {
         let options = ShapeCastOptions {
-            max_time_of_impact: 10.0,
+            max_time_of_impact: probe.distance,
             target_distance: 0.0,
             stop_at_penetration: true,
             compute_impact_geometry_on_penetration: true,
@@ -118,28 +372,40 @@ fn compute_is_grounded(
             ..QueryFilter::default()
         };
 
-        if let Some((_entity, _hit)) = rapier_context.cast_shape(
-            global_transform.translation().xy(),
-            Rot::default(),
-            -Vec2::Y,
-            &Collider::ball(64f32),
-            options,
-            filter,
-        ) {
-            is_grounded.0 = true;
-        } else {
-            is_grounded.0 = false;
-        }
+        let ground = rapier_context
+            .cast_shape(
+                global_transform.translation().xy(),
+                Rot::default(),
+                -Vec2::Y,
+                &probe.shape,
+                options,
+                filter,
+            )
+            .map(|(ground, hit)| {
+                let normal = hit.details.map_or(Vec2::Y, |details| details.normal1);
+                (ground, normal)
+            })
+            // Too steep to stand on.
+            .filter(|(_, normal)| normal.angle_between(Vec2::Y).abs() <= probe.max_slope);
+
+        *grounded = match ground {
+            Some((ground, normal)) => Grounded {
+                entity: Some(ground),
+                normal,
+                material: q_material.get(ground).ok().map(|material| material.0),
+            },
+            None => Grounded::default(),
+        };
     }
 }
 
-fn compute_can_jump(
+pub(super) fn compute_can_jump(
     time: Res<Time>,
-    mut query: Query<(&mut JumpDelay, &mut CoyoteTime, &IsGrounded, &mut CanJump)>,
+    mut query: Query<(&mut JumpDelay, &mut CoyoteTime, &Grounded, &mut CanJump)>,
 ) {
-    for (mut jump_delay, mut coyote_time, is_grounded, mut can_jump) in query.iter_mut() {
+    for (mut jump_delay, mut coyote_time, grounded, mut can_jump) in query.iter_mut() {
         jump_delay.0.tick(time.delta());
-        if is_grounded.0 {
+        if grounded.on_ground() {
             if jump_delay.0.finished() {
                 coyote_time.0.reset();
             }
@@ -153,18 +419,35 @@ fn compute_can_jump(
     }
 }
 
-fn apply_movement(
+/// Remember jump presses, before anything that jumps looks at the [`JumpBuffer`] this tick.
+pub(super) fn update_jump_buffer(
     time: Res<Time>,
+    mut query: Query<(&MovementController, &JumpSettings, &mut JumpBuffer)>,
+) {
+    for (controller, settings, mut buffer) in &mut query {
+        let held = controller.0.y > 0.01f32;
+        if held && !buffer.held {
+            buffer.timer = Timer::from_seconds(settings.buffer, TimerMode::Once);
+        } else {
+            buffer.timer.tick(time.delta());
+        }
+        buffer.released = !held && buffer.held;
+        buffer.held = held;
+    }
+}
+
+pub(super) fn apply_movement(
     mut movement_query: Query<(
         Entity,
         &MovementController,
         &Movement,
+        &JumpSettings,
         &CanJump,
+        &mut JumpBuffer,
         &mut Velocity,
         &mut JumpDelay,
         &mut CoyoteTime,
         &mut GravityScale,
-        &mut Transform,
     )>,
     mut jump_event: EventWriter<JumpEvent>,
 ) {
@@ -172,31 +455,35 @@ fn apply_movement(
         entity,
         controller,
         movement,
+        settings,
         can_jump,
+        mut buffer,
         mut velocity,
         mut jump_delay,
         mut coyote_time,
         mut gravity,
-        mut transform,
     ) in &mut movement_query
     {
-        let wanted_velocity = movement.speed * controller.0.x;
-        transform.translation.x += wanted_velocity * time.delta_seconds();
-        if controller.0.y > 0.01f32 {
-            if can_jump.0 {
-                jump_delay.0.reset();
-                let dur = coyote_time.0.remaining();
-                coyote_time.0.tick(dur);
-                velocity.linvel.y = 600.0;
-                jump_event.send(JumpEvent(entity));
-            }
-            if velocity.linvel.y > 0.1f32 {
-                gravity.0 = 0.5f32;
-            } else {
-                gravity.0 = 1f32;
-            }
-        } else if controller.0.y < 0.01f32 {
-            gravity.0 = 1.5f32;
+        velocity.linvel.x = movement.speed * controller.0.x;
+
+        let JumpBuffer { held, released, .. } = *buffer;
+        if buffer.pending() && can_jump.0 {
+            buffer.consume();
+            jump_delay.0.reset();
+            let dur = coyote_time.0.remaining();
+            coyote_time.0.tick(dur);
+            velocity.linvel.y = settings.velocity;
+            jump_event.send(JumpEvent(entity));
+        } else if released && velocity.linvel.y > 0.0 {
+            velocity.linvel.y *= settings.release_cut;
         }
+
+        gravity.0 = if controller.0.y < -0.01f32 {
+            settings.fast_fall_gravity
+        } else if held && velocity.linvel.y > 0.1f32 {
+            settings.rising_gravity
+        } else {
+            settings.falling_gravity
+        };
     }
 }
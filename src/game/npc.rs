@@ -0,0 +1,137 @@
+//! What non-playable characters do while the level is played.
+//!
+//! Each NPC follows the [`NpcBehavior`] it was spawned with.
+//! Behaviors pause during cutscenes, which move NPCs on their own.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{animation::SpriteAnimator, spawn::player::Player};
+use crate::{screen::PlayingState, AppSet};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Npc>();
+    app.register_type::<NpcBehavior>();
+    app.add_systems(
+        Update,
+        update_npc_behaviors
+            .in_set(AppSet::Update)
+            .run_if(in_state(PlayingState::Gameplay)),
+    );
+}
+
+/// A non-playable character.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct Npc {
+    /// Index of the waypoint a patrolling NPC is walking to.
+    next_waypoint: usize,
+}
+
+#[derive(Component, Debug, Clone, Default, PartialEq, Reflect, Serialize, Deserialize)]
+#[reflect(Component)]
+pub enum NpcBehavior {
+    /// Stand still.
+    #[default]
+    Idle,
+    /// Turn towards the player when they are closer than `radius`.
+    FacePlayer { radius: f32 },
+    /// Face the player and wave at them when they are closer than `radius`.
+    Wave { radius: f32 },
+    /// Walk to `x`, then stand there.
+    WalkTo {
+        x: f32,
+        #[serde(default = "NpcBehavior::default_speed")]
+        speed: f32,
+    },
+    /// Walk back and forth between these positions along the x axis.
+    Patrol {
+        waypoints: Vec<f32>,
+        #[serde(default = "NpcBehavior::default_speed")]
+        speed: f32,
+    },
+}
+
+impl NpcBehavior {
+    fn default_speed() -> f32 {
+        150.0
+    }
+}
+
+/// Move towards `x` by at most `step`, facing where the NPC goes.
+/// Returns whether it got there.
+fn walk_towards(transform: &mut Transform, sprite: &mut Sprite, x: f32, step: f32) -> bool {
+    let distance = x - transform.translation.x;
+    if distance.abs() <= step {
+        transform.translation.x = x;
+        return true;
+    }
+    transform.translation.x += step * distance.signum();
+    sprite.flip_x = distance < 0.0;
+    false
+}
+
+fn update_npc_behaviors(
+    time: Res<Time>,
+    q_player: Query<&Transform, With<Player>>,
+    mut query: Query<
+        (
+            &mut Npc,
+            &NpcBehavior,
+            &mut Transform,
+            &mut Sprite,
+            &mut SpriteAnimator,
+        ),
+        Without<Player>,
+    >,
+) {
+    let player_x = q_player
+        .get_single()
+        .ok()
+        .map(|transform| transform.translation.x);
+    for (mut npc, behavior, mut transform, mut sprite, mut animator) in &mut query {
+        // Horizontal distance to the player, if they are closer than `radius`.
+        let player_near = |radius: f32, transform: &Transform| {
+            player_x
+                .map(|x| x - transform.translation.x)
+                .filter(|distance| distance.abs() <= radius)
+        };
+        match behavior {
+            // Animations started by triggers and cutscenes keep playing.
+            NpcBehavior::Idle => {}
+            NpcBehavior::FacePlayer { radius } => {
+                if let Some(distance) = player_near(*radius, &transform) {
+                    sprite.flip_x = distance < 0.0;
+                }
+            }
+            NpcBehavior::Wave { radius } => match player_near(*radius, &transform) {
+                Some(distance) => {
+                    sprite.flip_x = distance < 0.0;
+                    animator.play("wave");
+                }
+                None => animator.play("idle"),
+            },
+            NpcBehavior::WalkTo { x, speed } => {
+                let arrived = walk_towards(
+                    &mut transform,
+                    &mut sprite,
+                    *x,
+                    speed * time.delta_seconds(),
+                );
+                animator.play(if arrived { "idle" } else { "run" });
+            }
+            NpcBehavior::Patrol { waypoints, speed } => {
+                if waypoints.is_empty() {
+                    animator.play("idle");
+                    continue;
+                }
+                let target = waypoints[npc.next_waypoint % waypoints.len()];
+                let step = speed * time.delta_seconds();
+                if walk_towards(&mut transform, &mut sprite, target, step) {
+                    npc.next_waypoint = (npc.next_waypoint + 1) % waypoints.len();
+                }
+                animator.play("run");
+            }
+        }
+    }
+}
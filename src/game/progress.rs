@@ -0,0 +1,70 @@
+//! Which level is being played, which levels have been completed,
+//! and what happened during the current run.
+
+use bevy::{prelude::*, utils::HashSet};
+
+use super::assets::LevelKey;
+use crate::screen::Screen;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<CurrentLevel>();
+    app.init_resource::<CurrentLevel>();
+    app.init_resource::<LevelProgress>();
+    app.init_resource::<StoryFlags>();
+    app.add_systems(OnEnter(Screen::Playing), clear_story_flags);
+}
+
+/// The level spawned when entering [`Screen::Playing`](crate::screen::Screen::Playing).
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Resource)]
+pub struct CurrentLevel(pub LevelKey);
+
+impl Default for CurrentLevel {
+    fn default() -> Self {
+        Self(LevelKey::ALL[0])
+    }
+}
+
+/// Levels the player has reached the end of.
+#[derive(Resource, Debug, Default)]
+pub struct LevelProgress {
+    completed: HashSet<LevelKey>,
+}
+
+impl LevelProgress {
+    pub fn complete(&mut self, level: LevelKey) {
+        self.completed.insert(level);
+    }
+
+    pub fn is_completed(&self, level: LevelKey) -> bool {
+        self.completed.contains(&level)
+    }
+
+    /// The first level is always unlocked, the others once the previous one is completed.
+    pub fn is_unlocked(&self, level: LevelKey) -> bool {
+        match level.previous() {
+            Some(previous) => self.is_completed(previous),
+            None => true,
+        }
+    }
+}
+
+/// Story beats reached during the current run, set by
+/// [`TriggerAction::SetStoryFlag`](super::trigger::actions::TriggerAction::SetStoryFlag).
+/// Triggers can wait for one with [`OnTrigger::requires_flag`](super::trigger::OnTrigger::requires_flag).
+#[derive(Resource, Debug, Default)]
+pub struct StoryFlags(HashSet<String>);
+
+impl StoryFlags {
+    pub fn set(&mut self, flag: impl Into<String>) {
+        self.0.insert(flag.into());
+    }
+
+    pub fn is_set(&self, flag: &str) -> bool {
+        self.0.contains(flag)
+    }
+}
+
+fn clear_story_flags(mut story_flags: ResMut<StoryFlags>) {
+    story_flags.0.clear();
+}
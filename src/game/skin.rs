@@ -0,0 +1,140 @@
+//! Skins change how a character looks, and what it can do.
+
+use bevy::{prelude::*, utils::HashMap};
+
+use super::{
+    ability::{Dash, DoubleJump, WallJump},
+    animation::SpriteAnimator,
+    assets::{sheet::AnimationSheet, HandleMap, ImageKey, SheetKey},
+    movement::Movement,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Skin>();
+    app.init_resource::<SkinDefinitions>();
+}
+
+/// The skin a character is wearing.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub struct Skin(pub ImageKey);
+
+/// The stats and abilities that come with a skin.
+#[derive(Debug, Clone)]
+pub struct SkinDefinition {
+    /// Layout and animations of the skin's image.
+    pub sheet: SheetKey,
+    /// See [`Movement::speed`].
+    pub speed: f32,
+    pub double_jump: Option<DoubleJump>,
+    pub wall_jump: Option<WallJump>,
+    pub dash: Option<Dash>,
+}
+
+impl Default for SkinDefinition {
+    fn default() -> Self {
+        Self {
+            sheet: SheetKey::Character,
+            speed: 420.0,
+            double_jump: None,
+            wall_jump: None,
+            dash: None,
+        }
+    }
+}
+
+/// What each skin does. Skins not listed here only change the look.
+#[derive(Resource, Debug)]
+pub struct SkinDefinitions(pub HashMap<ImageKey, SkinDefinition>);
+
+impl Default for SkinDefinitions {
+    fn default() -> Self {
+        let skins = [
+            (
+                ImageKey::Job,
+                SkinDefinition {
+                    sheet: SheetKey::Job,
+                    dash: Some(Dash::new(1400.0, 0.15, 0.6)),
+                    ..default()
+                },
+            ),
+            (
+                ImageKey::Dev,
+                SkinDefinition {
+                    wall_jump: Some(WallJump::new(500.0, 0.2)),
+                    ..default()
+                },
+            ),
+            (
+                ImageKey::SuperBevy,
+                SkinDefinition {
+                    speed: 520.0,
+                    double_jump: Some(DoubleJump::new(1)),
+                    ..default()
+                },
+            ),
+        ];
+        Self(skins.into_iter().collect())
+    }
+}
+
+impl SkinDefinitions {
+    pub fn get(&self, key: ImageKey) -> SkinDefinition {
+        self.0.get(&key).cloned().unwrap_or_default()
+    }
+}
+
+/// Give `entity` the look and animations of a skin.
+/// Characters that can move also get its stats, and lose the abilities of their previous skin.
+pub fn apply_skin(world: &mut World, entity: Entity, key: ImageKey) {
+    let definition = world.resource::<SkinDefinitions>().get(key);
+    let sheet = world.resource::<HandleMap<SheetKey>>()[&definition.sheet].clone_weak();
+    // Sheets exported with their image use it rather than the skin's own.
+    let image = world
+        .resource::<Assets<AnimationSheet>>()
+        .get(&sheet)
+        .and_then(|sheet| sheet.image.clone())
+        .unwrap_or_else(|| world.resource::<HandleMap<ImageKey>>()[&key].clone_weak());
+    let Some(mut entity) = world.get_entity_mut(entity) else {
+        return;
+    };
+    entity.insert((Skin(key), image));
+    if let Some(mut animator) = entity.get_mut::<SpriteAnimator>() {
+        animator.sheet = sheet;
+    }
+    let Some(mut movement) = entity.get_mut::<Movement>() else {
+        return;
+    };
+    movement.speed = definition.speed;
+    set_ability(&mut entity, definition.double_jump, |current, new| {
+        current.charges = new.charges;
+    });
+    set_ability(&mut entity, definition.wall_jump, |current, new| {
+        current.push = new.push;
+        current.lock = new.lock;
+    });
+    set_ability(&mut entity, definition.dash, |current, new| {
+        current.speed = new.speed;
+        current.duration = new.duration;
+        current.cooldown = new.cooldown;
+    });
+}
+
+/// Give an ability to `entity`, or take it away.
+/// An ability it already has only gets new settings,
+/// so applying a skin again doesn't refill charges or reset cooldowns.
+fn set_ability<T: Component>(
+    entity: &mut EntityWorldMut,
+    ability: Option<T>,
+    configure: impl FnOnce(&mut T, T),
+) {
+    let Some(ability) = ability else {
+        entity.remove::<T>();
+        return;
+    };
+    if let Some(mut current) = entity.get_mut::<T>() {
+        configure(&mut current, ability);
+        return;
+    }
+    entity.insert(ability);
+}
@@ -0,0 +1,361 @@
+//! Stream level content in and out in chunks along the x axis,
+//! so only what is around the [`MainCamera`] is alive.
+
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+use crate::{
+    game::{
+        assets::level::{GroundSegment, Level, Sign, TriggerZone},
+        generator::Segment,
+        tags::{GroupOp, Tag},
+        trigger::TriggerState,
+    },
+    screen::Screen,
+    AppSet, MainCamera,
+};
+
+use super::{level::spawn_chunk_content, npc::SpawnNpc};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<ChunkStreaming>();
+    app.register_type::<Chunk>();
+    app.register_type::<ChunkTrigger>();
+    app.init_resource::<ChunkStreaming>();
+    app.add_systems(
+        Update,
+        stream_chunks
+            .in_set(AppSet::Update)
+            .run_if(in_state(Screen::Playing)),
+    );
+}
+
+/// Settings for streaming level chunks around the camera.
+#[derive(Resource, Debug, Clone, Reflect)]
+#[reflect(Resource)]
+pub struct ChunkStreaming {
+    /// Width of a chunk.
+    pub chunk_width: f32,
+    /// Chunks closer than this to the edges of the camera view are spawned.
+    /// They are despawned once they are further than `margin + chunk_width`,
+    /// so walking back and forth on a chunk border doesn't respawn it every frame.
+    pub margin: f32,
+}
+
+impl Default for ChunkStreaming {
+    fn default() -> Self {
+        Self {
+            chunk_width: 2000.0,
+            margin: 1000.0,
+        }
+    }
+}
+
+/// The chunk an entity was spawned with.
+/// It is despawned with that chunk, even if it has moved to another one since.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub struct Chunk(pub i32);
+
+/// Index of a trigger zone among the triggers of its [`Chunk`],
+/// identifying it when the chunk is spawned again.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub struct ChunkTrigger(pub usize);
+
+/// Level content, either for a whole level or for a single chunk.
+#[derive(Debug, Clone, Default)]
+pub struct ChunkContent {
+    pub ground: Vec<GroundSegment>,
+    pub signs: Vec<Sign>,
+    pub npcs: Vec<SpawnNpc>,
+    pub triggers: Vec<TriggerZone>,
+}
+
+impl From<&Level> for ChunkContent {
+    fn from(level: &Level) -> Self {
+        Self {
+            ground: level.ground.clone(),
+            signs: level.signs.clone(),
+            npcs: level.npcs.clone(),
+            triggers: level.triggers.clone(),
+        }
+    }
+}
+
+impl From<Segment> for ChunkContent {
+    fn from(segment: Segment) -> Self {
+        Self {
+            ground: segment.ground,
+            signs: segment.signs,
+            npcs: segment.npcs,
+            triggers: segment.triggers,
+        }
+    }
+}
+
+/// All the content of a level, split in chunks.
+#[derive(Component, Debug)]
+pub struct LevelStream {
+    chunk_width: f32,
+    chunks: HashMap<i32, ChunkContent>,
+    loaded: HashSet<i32>,
+    /// Groups despawned by [`apply_group_op`],
+    /// which must not come back when their chunk is spawned again.
+    consumed: HashSet<Tag>,
+    /// State of the triggers of unloaded chunks, by chunk and trigger index,
+    /// so once-only triggers and cooldowns survive their chunk being spawned again.
+    trigger_states: HashMap<(i32, usize), TriggerState>,
+}
+
+impl LevelStream {
+    pub fn new(chunk_width: f32) -> Self {
+        Self {
+            chunk_width,
+            chunks: default(),
+            loaded: default(),
+            consumed: default(),
+            trigger_states: default(),
+        }
+    }
+
+    fn chunk_index(&self, x: f32) -> i32 {
+        (x / self.chunk_width).floor() as i32
+    }
+
+    fn chunk_bounds(&self, index: i32) -> (f32, f32) {
+        let min = index as f32 * self.chunk_width;
+        (min, min + self.chunk_width)
+    }
+
+    pub fn is_consumed(&self, tags: &[Tag]) -> bool {
+        tags.iter().any(|tag| self.consumed.contains(tag))
+    }
+
+    pub fn consume(&mut self, tag: Tag) {
+        self.consumed.insert(tag);
+    }
+
+    /// Index in its chunk of the first trigger of `content`,
+    /// which is either the whole chunk or the part of it that was just added.
+    pub fn first_trigger_index(&self, chunk: i32, content: &ChunkContent) -> usize {
+        self.chunks
+            .get(&chunk)
+            .map_or(0, |chunk| chunk.triggers.len())
+            .saturating_sub(content.triggers.len())
+    }
+
+    /// The state a trigger had when its chunk was unloaded.
+    pub fn trigger_state(&self, chunk: i32, index: usize) -> Option<&TriggerState> {
+        self.trigger_states.get(&(chunk, index))
+    }
+
+    /// Split `content` into chunks.
+    /// Returns the parts of it that fall into chunks already spawned, by chunk,
+    /// which should be spawned right away.
+    pub fn add(&mut self, content: ChunkContent) -> HashMap<i32, ChunkContent> {
+        let mut late: HashMap<i32, ChunkContent> = HashMap::new();
+        for ground in content.ground {
+            for piece in self.split_ground(&ground) {
+                let index = self.chunk_index(piece.position.x);
+                if self.loaded.contains(&index) {
+                    late.entry(index).or_default().ground.push(piece.clone());
+                }
+                self.chunks.entry(index).or_default().ground.push(piece);
+            }
+        }
+        for sign in content.signs {
+            let index = self.chunk_index(sign.position.x);
+            if self.loaded.contains(&index) {
+                late.entry(index).or_default().signs.push(sign.clone());
+            }
+            self.chunks.entry(index).or_default().signs.push(sign);
+        }
+        for npc in content.npcs {
+            let index = self.chunk_index(npc.position.x);
+            if self.loaded.contains(&index) {
+                late.entry(index).or_default().npcs.push(npc.clone());
+            }
+            self.chunks.entry(index).or_default().npcs.push(npc);
+        }
+        for zone in content.triggers {
+            let index = self.chunk_index(zone.position.x);
+            if self.loaded.contains(&index) {
+                late.entry(index).or_default().triggers.push(zone.clone());
+            }
+            self.chunks.entry(index).or_default().triggers.push(zone);
+        }
+        late
+    }
+
+    /// Cut a ground segment at chunk borders.
+    fn split_ground(&self, ground: &GroundSegment) -> Vec<GroundSegment> {
+        let min = ground.position.x - ground.half_size.x;
+        let max = ground.position.x + ground.half_size.x;
+        (self.chunk_index(min)..=self.chunk_index(max))
+            .filter_map(|index| {
+                let (chunk_min, chunk_max) = self.chunk_bounds(index);
+                let (min, max) = (min.max(chunk_min), max.min(chunk_max));
+                (max > min).then(|| GroundSegment {
+                    position: Vec2::new((min + max) / 2.0, ground.position.y),
+                    half_size: Vec2::new((max - min) / 2.0, ground.half_size.y),
+                    tags: ground.tags.clone(),
+                    material: ground.material,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Apply `op` to all entities with `tag`.
+/// When it despawns them, they stay gone when their chunk is spawned again.
+pub fn apply_group_op(world: &mut World, tag: Tag, op: GroupOp) {
+    let despawns = op.despawns();
+    op.apply(world, tag);
+    if despawns {
+        for mut stream in world.query::<&mut LevelStream>().iter_mut(world) {
+            stream.consume(tag);
+        }
+    }
+}
+
+fn stream_chunks(
+    mut commands: Commands,
+    settings: Res<ChunkStreaming>,
+    q_camera: Query<(&Transform, &OrthographicProjection), With<MainCamera>>,
+    mut q_stream: Query<&mut LevelStream>,
+    q_chunk_entities: Query<(Entity, &Chunk, Option<(&ChunkTrigger, &TriggerState)>)>,
+) {
+    let Ok((camera, projection)) = q_camera.get_single() else {
+        return;
+    };
+    let view_min = camera.translation.x + projection.area.min.x;
+    let view_max = camera.translation.x + projection.area.max.x;
+
+    for mut stream in &mut q_stream {
+        let stream = &mut *stream;
+
+        let unload_min = stream.chunk_index(view_min - settings.margin - settings.chunk_width);
+        let unload_max = stream.chunk_index(view_max + settings.margin + settings.chunk_width);
+        let to_unload: Vec<i32> = stream
+            .loaded
+            .iter()
+            .copied()
+            .filter(|index| !(unload_min..=unload_max).contains(index))
+            .collect();
+        for index in to_unload {
+            stream.loaded.remove(&index);
+            for (entity, chunk, trigger) in &q_chunk_entities {
+                if chunk.0 != index {
+                    continue;
+                }
+                if let Some((ChunkTrigger(trigger), state)) = trigger {
+                    stream
+                        .trigger_states
+                        .insert((index, *trigger), state.detached());
+                }
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+
+        let load_min = stream.chunk_index(view_min - settings.margin);
+        let load_max = stream.chunk_index(view_max + settings.margin);
+        for index in load_min..=load_max {
+            if !stream.loaded.insert(index) {
+                continue;
+            }
+            if let Some(content) = stream.chunks.get(&index) {
+                spawn_chunk_content(&mut commands, index, content, stream);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        game::{
+            cutscene::{self, play_cutscene, Cutscene, CutsceneStep},
+            tags::{TagIndex, Tags},
+        },
+        screen::PlayingState,
+    };
+
+    const CHUNK_WIDTH: f32 = 100.0;
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, cutscene::plugin));
+        app.init_resource::<TagIndex>();
+        app.init_resource::<NextState<PlayingState>>();
+        app.insert_resource(ChunkStreaming {
+            chunk_width: CHUNK_WIDTH,
+            margin: 0.0,
+        });
+        app.add_systems(Update, stream_chunks);
+        app.world_mut().spawn((
+            MainCamera,
+            Transform::default(),
+            OrthographicProjection::default(),
+        ));
+        app
+    }
+
+    fn move_camera(app: &mut App, x: f32) {
+        let mut camera = app
+            .world_mut()
+            .query_filtered::<&mut Transform, With<MainCamera>>()
+            .single_mut(app.world_mut());
+        camera.translation.x = x;
+        app.update();
+    }
+
+    fn tagged(app: &mut App, tag: Tag) -> usize {
+        app.world_mut()
+            .query::<&Tags>()
+            .iter(app.world())
+            .filter(|tags| tags.contains(tag))
+            .count()
+    }
+
+    #[test]
+    fn group_despawned_by_cutscene_stays_gone_when_chunk_reloads() {
+        let mut app = app();
+        let fans = Tag::new("fans");
+        let mut stream = LevelStream::new(CHUNK_WIDTH);
+        stream.add(ChunkContent {
+            ground: vec![GroundSegment {
+                position: Vec2::new(50.0, 0.0),
+                half_size: Vec2::new(10.0, 10.0),
+                tags: vec![fans],
+                material: None,
+            }],
+            triggers: vec![TriggerZone {
+                name: "Trigger fans".to_string(),
+                position: Vec2::new(50.0, 0.0),
+                radius: 10.0,
+                tags: vec![fans],
+                ..default()
+            }],
+            ..default()
+        });
+        app.world_mut().spawn(stream);
+
+        move_camera(&mut app, 50.0);
+        assert_eq!(tagged(&mut app, fans), 2);
+
+        play_cutscene(
+            app.world_mut(),
+            Cutscene(vec![CutsceneStep::Group(fans, GroupOp::Despawn)]),
+        );
+        app.update();
+        assert_eq!(tagged(&mut app, fans), 0);
+
+        move_camera(&mut app, 1000.0);
+        move_camera(&mut app, 50.0);
+        assert_eq!(tagged(&mut app, fans), 0);
+    }
+}
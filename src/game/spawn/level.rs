@@ -1,333 +1,278 @@
-//! Spawn the main level by triggering other observers.
+//! Spawn a level from its [`Level`] description by triggering other observers.
 
-use bevy::prelude::*;
+use bevy::{ecs::system::EntityCommands, prelude::*};
 use bevy_rapier2d::geometry::{Collider, Sensor};
 
 use crate::{
     game::{
-        assets::{HandleMap, ImageKey},
-        trigger::{OnTrigger, OnTriggerEvent},
+        assets::level::{GroundSegment, Level, Sign, TriggerZone},
+        checkpoint::KillPlane,
+        cutscene::LevelCutscenes,
+        generator::LevelGenerator,
+        movement::{RunModeSetting, SurfaceMaterial},
+        tags::{Tag, Tags},
+        trigger::{
+            actions::{TriggerActions, TriggerExitActions},
+            OnTrigger, TriggerState,
+        },
     },
     screen::Screen,
+    AppSet,
 };
 
 use super::{
-    npc::{DespawnId, SpawnNpc},
-    player::SpawnPlayer,
+    chunk::{Chunk, ChunkContent, ChunkStreaming, ChunkTrigger, LevelStream},
+    player::{Player, SpawnPlayer},
 };
 
 pub(super) fn plugin(app: &mut App) {
+    app.register_type::<LevelEntity>();
     app.observe(spawn_level);
-    app.observe(change_skin);
-    app.observe(trigger_react_despawn);
-    app.observe(trigger_game_over);
+    app.add_systems(
+        Update,
+        (respawn_modified_level, extend_generated_level)
+            .chain()
+            .in_set(AppSet::Update)
+            .run_if(in_state(Screen::Playing)),
+    );
 }
 
+/// Spawn the level described by the given [`Level`] asset.
 #[derive(Event, Debug)]
-pub struct SpawnLevel;
-
-#[derive(Component, Debug)]
-pub struct TriggerGameOver;
+pub struct SpawnLevel(pub Handle<Level>);
 
+/// Keeps track of which [`Level`] is being played, so it can be respawned when modified.
 #[derive(Component, Debug)]
-pub struct SkinToApply {
-    pub key: ImageKey,
-}
+struct LevelRoot(Handle<Level>);
 
-/// Applied to [`OnTrigger`], this will despawn entities with [`DespawnId`] with that string.
+/// How far ahead of the player a [`LevelGenerator`] emits segments.
 #[derive(Component, Debug)]
-pub struct Despawner(pub String);
+struct GeneratorLookahead(f32);
 
-fn spawn_level(_trigger: Trigger<SpawnLevel>, mut commands: Commands) {
-    // The only thing we have in our level is a player,
-    // but add things like walls etc. here.
-    let ground_size = 1000.0;
-    let ground_height = 50.0;
-    let mut position = 0.0;
-    for i in 0..100 {
-        commands.spawn((
-            TransformBundle::from(Transform::from_xyz(
-                i as f32 * ground_size,
-                0.5 * -ground_height,
-                0.0,
-            )),
-            Collider::cuboid(ground_size, ground_height),
-        ));
-    }
+/// Marker for everything spawned from a [`Level`] description.
+/// These entities are despawned and spawned again when the level is hot-reloaded.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct LevelEntity;
 
-    let text_style = TextStyle {
-        font_size: 30.0,
-        ..default()
+fn spawn_level(
+    trigger: Trigger<SpawnLevel>,
+    mut commands: Commands,
+    levels: Res<Assets<Level>>,
+    streaming: Res<ChunkStreaming>,
+    run_mode_setting: Res<RunModeSetting>,
+) {
+    let Some(level) = levels.get(&trigger.event().0) else {
+        warn!("Tried to spawn a level that is not loaded.");
+        return;
     };
 
-    commands.spawn((Text2dBundle {
-        text: Text::from_section("use arrow keys ->", text_style.clone())
-            .with_justify(JustifyText::Center),
-        transform: Transform::from_translation(Vec3::new(
-            position - 120.0,
-            64.0 + 128.0 + 32.0 + 40.0,
-            0.0,
-        )),
-        ..default()
-    },));
-
-    //
-    // bevy introduction
-    //
-    position += 700.0;
-    let despawn_id = "bevy fans".to_string();
-    commands.trigger(SpawnPlayer);
-    commands.trigger(SpawnNpc {
-        image_key: ImageKey::Mockersf,
-        position: Vec2::new(position, 64.0 + 32.0),
-        despawn_id: Some(despawn_id.clone()),
-    });
-    commands.trigger(SpawnNpc {
-        image_key: ImageKey::Joshua,
-        position: Vec2::new(position + 120.0, 64.0 + 32.0),
-        despawn_id: Some(despawn_id.clone()),
-    });
     commands.spawn((
-        Name::new("Trigger fans"),
-        Collider::ball(320.0),
-        Sensor,
-        SpatialBundle {
-            transform: Transform::from_translation(
-                Vec2::new(position + 300.0, 64.0 + 32.0).extend(0f32),
-            ),
-            ..default()
-        },
-        OnTrigger,
-        Despawner(despawn_id.clone()),
-        SkinToApply {
-            key: ImageKey::Bavy,
-        },
-        DespawnId(despawn_id.clone()),
+        Name::new("Level"),
+        LevelRoot(trigger.event().0.clone_weak()),
+        StateScoped(Screen::Playing),
     ));
-    commands.spawn((Text2dBundle {
-        text: Text::from_section("Have you heard of Bevy ?", text_style.clone())
-            .with_justify(JustifyText::Center),
-        transform: Transform::from_translation(Vec3::new(position, 64.0 + 128.0 + 32.0, 0.0)),
-        ..default()
-    },));
-
-    for i in 0..3 {
-        //
-        // Job
-        //
-
-        let despawn_id = format!("bevy job {}", i);
-        position += 1300.0 + 400.0 * i as f32;
-        commands.trigger(SpawnNpc {
-            image_key: ImageKey::Job,
-            position: Vec2::new(position, 64.0 + 32.0),
-            despawn_id: Some(despawn_id.clone()),
-        });
-        commands.spawn((Text2dBundle {
-            text: Text::from_section(
-                [
-                    "Hey you look capable! What about getting a job?",
-                    "Wow your contributions are great! Let's make business together!",
-                    "Please help our company scale with Bevy!",
-                ][i.min(2)],
-                text_style.clone(),
-            )
-            .with_justify(JustifyText::Center),
-            transform: Transform::from_translation(Vec3::new(
-                position - 100.0,
-                64.0 + 128.0 + 32.0,
-                0.0,
-            )),
-            ..default()
-        },));
-        commands.spawn((
-            Name::new(despawn_id.clone()),
-            Collider::ball(320.0),
-            Sensor,
-            SpatialBundle {
-                transform: Transform::from_translation(
-                    Vec2::new(position + 300.0, 64.0 + 32.0).extend(0f32),
-                ),
-                ..default()
-            },
-            OnTrigger,
-            Despawner(despawn_id.clone()),
-            SkinToApply { key: ImageKey::Job },
-            DespawnId(despawn_id.clone()),
-        ));
+    commands.trigger(SpawnPlayer {
+        run_mode: level.run_mode.unwrap_or(run_mode_setting.0),
+    });
+    spawn_level_content(&mut commands, level, &streaming);
+}
 
-        //
-        // bevy dev
-        //
-        let despawn_id = format!("bevy dev {}", i);
-        position += 1400.0 + 600.0 * i as f32;
-        commands.trigger(SpawnNpc {
-            image_key: ImageKey::Dev,
-            position: Vec2::new(position, 64.0 + 32.0),
-            despawn_id: Some(despawn_id.clone()),
-        });
-        commands.spawn((Text2dBundle {
-            text: Text::from_section(
-                [
-                    "A bevy user is a bevy developer who doesn't know it yet.",
-                    "There's so many areas to bevy, let's make it even better!",
-                    "SME is for Subject Matter Experts, working with them is great!",
-                ][i.min(2)],
-                text_style.clone(),
-            )
-            .with_justify(JustifyText::Center),
-            transform: Transform::from_translation(Vec3::new(
-                position - 120.0,
-                64.0 + 128.0 + 32.0,
-                0.0,
-            )),
-            ..default()
-        },));
-        commands.spawn((
-            Name::new(despawn_id.clone()),
-            Collider::ball(320.0),
-            Sensor,
-            SpatialBundle {
-                transform: Transform::from_translation(
-                    Vec2::new(position + 300.0, 64.0 + 32.0).extend(0f32),
-                ),
-                ..default()
-            },
-            OnTrigger,
-            Despawner(despawn_id.clone()),
-            SkinToApply { key: ImageKey::Dev },
-            DespawnId(despawn_id.clone()),
-        ));
+/// When a level file changes on disk, replace its content in place.
+/// The player is not part of the level content, so it keeps its position and skin.
+fn respawn_modified_level(
+    mut commands: Commands,
+    mut asset_events: EventReader<AssetEvent<Level>>,
+    levels: Res<Assets<Level>>,
+    q_root: Query<&LevelRoot>,
+    q_level_entities: Query<Entity, With<LevelEntity>>,
+    streaming: Res<ChunkStreaming>,
+) {
+    for event in asset_events.read() {
+        let AssetEvent::Modified { id } = event else {
+            continue;
+        };
+        if !q_root.iter().any(|root| root.0.id() == *id) {
+            continue;
+        }
+        let Some(level) = levels.get(*id) else {
+            continue;
+        };
+        info!("Level modified, respawning it.");
+        for entity in &q_level_entities {
+            commands.entity(entity).despawn_recursive();
+        }
+        spawn_level_content(&mut commands, level, &streaming);
     }
-    position += 2000.0;
-    commands.spawn((
-        Name::new("Trigger super"),
-        Collider::ball(320.0),
-        Sensor,
-        SpatialBundle {
-            transform: Transform::from_translation(
-                Vec2::new(position + 300.0, 64.0 + 32.0).extend(0f32),
-            ),
-            ..default()
-        },
-        OnTrigger,
-        Despawner("superbevy".to_string()),
-        SkinToApply {
-            key: ImageKey::SuperBevy,
-        },
-        DespawnId("superbevy".to_string()),
-    ));
-    commands.spawn((Text2dBundle {
-        text: Text::from_section("Yeah that was the bevy cyle", text_style.clone())
-            .with_justify(JustifyText::Center),
-        transform: Transform::from_translation(Vec3::new(
-            position - 120.0,
-            64.0 + 128.0 + 32.0,
-            0.0,
-        )),
-        ..default()
-    },));
+}
 
-    position += 2300.0;
-    commands.spawn((Text2dBundle {
-        text: Text::from_section("Thanks for 'playing'", text_style.clone())
-            .with_justify(JustifyText::Center),
-        transform: Transform::from_translation(Vec3::new(
-            position - 120.0,
-            64.0 + 128.0 + 32.0,
-            0.0,
-        )),
-        ..default()
-    },));
-    position += 2600.0;
-    commands.spawn((Text2dBundle {
-        text: Text::from_section("When is editor ?", text_style.clone())
-            .with_justify(JustifyText::Center),
-        transform: Transform::from_translation(Vec3::new(
-            position - 120.0,
-            64.0 + 128.0 + 32.0,
-            0.0,
-        )),
-        ..default()
-    },));
-    position += 3000.0;
-    commands.spawn((Text2dBundle {
-        text: Text::from_section("Stop now it's over!", text_style.clone())
-            .with_justify(JustifyText::Center),
-        transform: Transform::from_translation(Vec3::new(
-            position - 120.0,
-            64.0 + 128.0 + 32.0,
-            0.0,
-        )),
-        ..default()
-    },));
+/// Spawn the entity streaming the level content around the camera,
+/// which may also extend the level with a generator.
+fn spawn_level_content(commands: &mut Commands, level: &Level, streaming: &ChunkStreaming) {
+    let mut stream = LevelStream::new(streaming.chunk_width);
+    stream.add(level.into());
 
-    position += 3500.0;
-    commands.spawn((
-        Name::new("Trigger gameover"),
-        Collider::ball(320.0),
-        Sensor,
-        SpatialBundle {
-            transform: Transform::from_translation(
-                Vec2::new(position + 800.0, 64.0 + 32.0).extend(0f32),
-            ),
-            ..default()
+    let mut entity = commands.spawn((
+        Name::new("Level stream"),
+        KillPlane {
+            height: level.kill_height,
         },
-        OnTrigger,
-        TriggerGameOver,
+        LevelCutscenes(level.cutscenes.clone()),
+        LevelEntity,
+        StateScoped(Screen::Playing),
     ));
-    commands.spawn((Text2dBundle {
-        text: Text::from_section(
-            "Hire me next year? Comment 'I hire you'!",
-            text_style.clone(),
-        )
-        .with_justify(JustifyText::Center),
-        transform: Transform::from_translation(Vec3::new(
-            position - 120.0,
-            64.0 + 128.0 + 32.0,
-            0.0,
-        )),
-        ..default()
-    },));
+    if let Some(settings) = &level.generator {
+        let seed = settings.seed.unwrap_or_else(rand::random);
+        info!("Generating level with seed {seed}.");
+        stream.add(ChunkContent {
+            signs: vec![Sign {
+                text: format!("Seed {seed}"),
+                position: Vec2::new(settings.start, 340.0),
+                font_size: 20.0,
+                tags: vec![],
+            }],
+            ..default()
+        });
+        entity.insert((
+            LevelGenerator::new(seed, settings.difficulty, settings.start),
+            GeneratorLookahead(settings.lookahead),
+        ));
+    }
+    entity.insert(stream);
 }
 
-pub fn change_skin(
-    trigger: Trigger<OnTriggerEvent>,
+/// Append generated segments ahead of the player as they run.
+fn extend_generated_level(
     mut commands: Commands,
-    q: Query<&SkinToApply>,
-    image_handles: Res<HandleMap<ImageKey>>,
+    mut q_generator: Query<(&mut LevelGenerator, &GeneratorLookahead, &mut LevelStream)>,
+    q_player: Query<&Transform, With<Player>>,
 ) {
-    dbg!("change_skin");
-    if let Ok(to_apply) = q.get(trigger.event().trigger) {
-        commands
-            .entity(trigger.event().other)
-            .insert(image_handles[&to_apply.key].clone_weak());
+    let Ok(player) = q_player.get_single() else {
+        return;
+    };
+    for (mut generator, lookahead, mut stream) in &mut q_generator {
+        while generator.cursor() < player.translation.x + lookahead.0 {
+            let late = stream.add(generator.next_segment().into());
+            for (index, content) in &late {
+                spawn_chunk_content(&mut commands, *index, content, &stream);
+            }
+        }
     }
 }
 
-pub fn trigger_react_despawn(
-    trigger: Trigger<OnTriggerEvent>,
-    q: Query<&Despawner>,
-    mut commands: Commands,
-    q_ids: Query<(Entity, &DespawnId)>,
+/// Spawn the content of a chunk, skipping groups that were already despawned by
+/// [`apply_group_op`](super::chunk::apply_group_op).
+pub(super) fn spawn_chunk_content(
+    commands: &mut Commands,
+    chunk: i32,
+    content: &ChunkContent,
+    stream: &LevelStream,
 ) {
-    dbg!("trigger_react_despawn");
-    if let Ok(to_despawn) = q.get(trigger.event().trigger) {
-        for (e, d) in q_ids.iter() {
-            if d.0 == to_despawn.0 {
-                commands.entity(e).despawn();
-            }
+    for ground in content
+        .ground
+        .iter()
+        .filter(|ground| !stream.is_consumed(&ground.tags))
+    {
+        spawn_ground(commands, chunk, ground);
+    }
+    for sign in content
+        .signs
+        .iter()
+        .filter(|sign| !stream.is_consumed(&sign.tags))
+    {
+        spawn_sign(commands, chunk, sign);
+    }
+    for npc in content
+        .npcs
+        .iter()
+        .filter(|npc| !stream.is_consumed(&npc.tags))
+    {
+        let entity = commands.spawn(Chunk(chunk)).id();
+        commands.trigger_targets(npc.clone(), entity);
+    }
+    let first_trigger = stream.first_trigger_index(chunk, content);
+    for (index, zone) in (first_trigger..).zip(&content.triggers) {
+        if stream.is_consumed(&zone.tags) {
+            continue;
         }
+        let state = stream
+            .trigger_state(chunk, index)
+            .cloned()
+            .unwrap_or_default();
+        spawn_trigger_zone(commands, chunk, index, zone, state);
+    }
+}
+
+fn spawn_ground(commands: &mut Commands, chunk: i32, ground: &GroundSegment) {
+    let mut entity = commands.spawn((
+        Name::new("Ground"),
+        TransformBundle::from(Transform::from_translation(ground.position.extend(0.0))),
+        Collider::cuboid(ground.half_size.x, ground.half_size.y),
+        Chunk(chunk),
+        LevelEntity,
+        StateScoped(Screen::Playing),
+    ));
+    insert_tags(&mut entity, &ground.tags);
+    if let Some(material) = ground.material {
+        entity.insert(SurfaceMaterial(material));
     }
 }
 
-pub fn trigger_game_over(
-    trigger: Trigger<OnTriggerEvent>,
-    q: Query<&TriggerGameOver>,
-    mut next_gamestate: ResMut<NextState<Screen>>,
+fn spawn_sign(commands: &mut Commands, chunk: i32, sign: &Sign) {
+    let mut entity = commands.spawn((
+        Name::new("Sign"),
+        Text2dBundle {
+            text: Text::from_section(
+                sign.text.clone(),
+                TextStyle {
+                    font_size: sign.font_size,
+                    ..default()
+                },
+            )
+            .with_justify(JustifyText::Center),
+            transform: Transform::from_translation(sign.position.extend(0.0)),
+            ..default()
+        },
+        Chunk(chunk),
+        LevelEntity,
+        StateScoped(Screen::Playing),
+    ));
+    insert_tags(&mut entity, &sign.tags);
+}
+
+fn spawn_trigger_zone(
+    commands: &mut Commands,
+    chunk: i32,
+    index: usize,
+    zone: &TriggerZone,
+    state: TriggerState,
 ) {
-    dbg!("change_skin");
-    if let Ok(_to_apply) = q.get(trigger.event().trigger) {
-        next_gamestate.set(Screen::Loading);
+    let mut entity = commands.spawn((
+        Name::new(zone.name.clone()),
+        Collider::ball(zone.radius),
+        Sensor,
+        SpatialBundle {
+            transform: Transform::from_translation(zone.position.extend(0.0)),
+            ..default()
+        },
+        OnTrigger {
+            policy: zone.policy,
+            filter: zone.filter,
+            requires_flag: zone.requires_flag.clone(),
+        },
+        state,
+        TriggerActions(zone.actions.clone()),
+        TriggerExitActions(zone.exit_actions.clone()),
+        Chunk(chunk),
+        ChunkTrigger(index),
+        LevelEntity,
+        StateScoped(Screen::Playing),
+    ));
+    insert_tags(&mut entity, &zone.tags);
+}
+
+fn insert_tags(entity: &mut EntityCommands, tags: &[Tag]) {
+    if !tags.is_empty() {
+        entity.insert(Tags(tags.to_vec()));
     }
 }
@@ -4,11 +4,12 @@
 
 use bevy::prelude::*;
 
+pub mod chunk;
 pub mod level;
-mod npc;
+pub mod npc;
 pub mod player;
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_plugins((level::plugin, player::plugin));
+    app.add_plugins((chunk::plugin, level::plugin, player::plugin));
     app.add_plugins(npc::plugin);
 }
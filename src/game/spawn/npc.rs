@@ -1,47 +1,56 @@
-//! Spawn the player.
+//! Spawn non-playable characters.
 
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     game::{
-        animation::PlayerAnimation,
-        assets::{HandleMap, ImageKey},
+        animation::SpriteAnimator,
+        assets::{HandleMap, ImageKey, SheetKey},
+        npc::{Npc, NpcBehavior},
+        tags::{Tag, Tags},
     },
     screen::Screen,
 };
 
+use super::level::LevelEntity;
+
 pub(super) fn plugin(app: &mut App) {
     app.observe(spawn_npc);
     app.register_type::<SpawnNpc>();
-    app.register_type::<DespawnId>();
 }
 
-#[derive(Event, Debug, Default, Reflect)]
+/// Spawn an NPC. When triggered on an entity, that entity becomes the NPC.
+#[derive(Event, Debug, Default, Clone, PartialEq, Reflect, Serialize, Deserialize)]
 pub struct SpawnNpc {
     pub image_key: ImageKey,
     pub position: Vec2,
-    pub despawn_id: Option<String>,
+    #[serde(default)]
+    #[reflect(ignore)]
+    pub tags: Vec<Tag>,
+    #[serde(default)]
+    pub behavior: NpcBehavior,
 }
 
-#[derive(Component, Debug, Reflect)]
-pub struct DespawnId(pub String);
-
 fn spawn_npc(
     _trigger: Trigger<SpawnNpc>,
     mut commands: Commands,
     image_handles: Res<HandleMap<ImageKey>>,
-    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    sheet_handles: Res<HandleMap<SheetKey>>,
 ) {
     // A texture atlas is a way to split one image with a grid into multiple sprites.
     // By attaching it to a [`SpriteBundle`] and providing an index, we can specify which section of the image we want to see.
     // We will use this to animate our player character. You can learn more about texture atlases in this example:
     // https://github.com/bevyengine/bevy/blob/latest/examples/2d/texture_atlas.rs
-    let layout =
-        TextureAtlasLayout::from_grid(UVec2::splat(128), 6, 2, Some(UVec2::splat(1)), None);
-    let texture_atlas_layout = texture_atlas_layouts.add(layout);
-    let player_animation = PlayerAnimation::new();
+    // The grid of the atlas comes from the animation sheet, and is set by the animator.
+    let animator = SpriteAnimator::new(sheet_handles[&SheetKey::Character].clone_weak(), "idle");
 
-    let mut new = commands.spawn((
+    let mut new = if _trigger.entity() == Entity::PLACEHOLDER {
+        commands.spawn_empty()
+    } else {
+        commands.entity(_trigger.entity())
+    };
+    new.insert((
         Name::new("NPC"),
         SpriteBundle {
             texture: image_handles[&_trigger.event().image_key].clone_weak(),
@@ -52,14 +61,14 @@ fn spawn_npc(
             },
             ..Default::default()
         },
-        TextureAtlas {
-            layout: texture_atlas_layout.clone(),
-            index: player_animation.get_atlas_index(),
-        },
-        player_animation,
+        TextureAtlas::default(),
+        animator,
+        Npc::default(),
+        _trigger.event().behavior.clone(),
+        LevelEntity,
         StateScoped(Screen::Playing),
     ));
-    if let Some(id) = &_trigger.event().despawn_id {
-        new.insert(DespawnId(id.to_string()));
+    if !_trigger.event().tags.is_empty() {
+        new.insert(Tags(_trigger.event().tags.clone()));
     }
 }
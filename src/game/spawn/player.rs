@@ -5,11 +5,18 @@ use bevy_rapier2d::prelude::*;
 
 use crate::{
     game::{
-        animation::PlayerAnimation,
-        assets::{HandleMap, ImageKey},
-        movement::{CanJump, CoyoteTime, IsGrounded, JumpDelay, Movement, MovementController},
+        animation::SpriteAnimator,
+        assets::{HandleMap, ImageKey, SheetKey},
+        checkpoint::RespawnPoint,
+        locomotion::Locomotion,
+        movement::{
+            interpolate_transforms, CanJump, CoyoteTime, GroundProbe, Grounded,
+            InterpolatedPosition, JumpBuffer, JumpDelay, JumpSettings, Movement,
+            MovementController, RunMode,
+        },
+        skin::apply_skin,
     },
-    screen::Screen,
+    screen::{PlayingState, Screen},
     MainCamera,
 };
 
@@ -19,15 +26,18 @@ pub(super) fn plugin(app: &mut App) {
     app.register_type::<UpdateCanJumpLabel>();
     app.add_systems(Update, update_can_jump_label);
     app.add_systems(
-        Update,
+        PostUpdate,
         follow_camera
-            .after(PhysicsSet::Writeback)
+            .run_if(in_state(PlayingState::Gameplay))
+            .after(interpolate_transforms)
             .before(TransformSystem::TransformPropagate),
     );
 }
 
 #[derive(Event, Debug)]
-pub struct SpawnPlayer;
+pub struct SpawnPlayer {
+    pub run_mode: RunMode,
+}
 
 #[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
 #[reflect(Component)]
@@ -41,48 +51,56 @@ fn spawn_player(
     _trigger: Trigger<SpawnPlayer>,
     mut commands: Commands,
     image_handles: Res<HandleMap<ImageKey>>,
-    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    sheet_handles: Res<HandleMap<SheetKey>>,
 ) {
     // A texture atlas is a way to split one image with a grid into multiple sprites.
     // By attaching it to a [`SpriteBundle`] and providing an index, we can specify which section of the image we want to see.
     // We will use this to animate our player character. You can learn more about texture atlases in this example:
     // https://github.com/bevyengine/bevy/blob/latest/examples/2d/texture_atlas.rs
-    let layout =
-        TextureAtlasLayout::from_grid(UVec2::splat(130), 6, 2, Some(UVec2::splat(1)), None);
-    let texture_atlas_layout = texture_atlas_layouts.add(layout);
-    let player_animation = PlayerAnimation::new();
+    // The grid of the atlas comes from the animation sheet, and is set by the animator.
+    let animator = SpriteAnimator::new(sheet_handles[&SheetKey::Character].clone_weak(), "idle");
+    let position = Vec2::new(0.0, 128.0);
+    let skin = ImageKey::Ducky;
 
-    commands.spawn((
-        Name::new("Player"),
-        Player,
-        SpriteBundle {
-            texture: image_handles[&ImageKey::Ducky].clone_weak(),
-            transform: Transform::from_scale(Vec2::new(1f32, 1f32).extend(1.0))
-                .with_translation(Vec3::new(0f32, 128f32, 0f32)),
-            ..Default::default()
-        },
-        TextureAtlas {
-            layout: texture_atlas_layout.clone(),
-            index: player_animation.get_atlas_index(),
-        },
-        MovementController::default(),
-        Movement { speed: 420.0 },
-        player_animation,
-        StateScoped(Screen::Playing),
-        (
-            RigidBody::Dynamic,
-            LockedAxes::ROTATION_LOCKED,
-            ActiveEvents::COLLISION_EVENTS,
-            Velocity::zero(),
-            Collider::ball(64f32),
-            GravityScale(1f32),
-            JumpDelay(Timer::from_seconds(0.25f32, TimerMode::Once)),
-            CoyoteTime(Timer::from_seconds(0.25f32, TimerMode::Once)),
-            CanJump(false),
-            IsGrounded(false),
-            ExternalImpulse::default(),
-        ),
-    ));
+    let player = commands
+        .spawn((
+            Name::new("Player"),
+            Player,
+            SpriteBundle {
+                texture: image_handles[&skin].clone_weak(),
+                transform: Transform::from_scale(Vec2::new(1f32, 1f32).extend(1.0))
+                    .with_translation(position.extend(0f32)),
+                ..Default::default()
+            },
+            TextureAtlas::default(),
+            MovementController::default(),
+            _trigger.event().run_mode,
+            Movement { speed: 420.0 },
+            JumpSettings::default(),
+            JumpBuffer::default(),
+            animator,
+            Locomotion::default(),
+            RespawnPoint { position, skin },
+            InterpolatedPosition::new(position.extend(0.0)),
+            StateScoped(Screen::Playing),
+            (
+                RigidBody::Dynamic,
+                LockedAxes::ROTATION_LOCKED,
+                ActiveEvents::COLLISION_EVENTS,
+                Velocity::zero(),
+                Collider::ball(64f32),
+                GravityScale(1f32),
+                JumpDelay(Timer::from_seconds(0.25f32, TimerMode::Once)),
+                CoyoteTime(Timer::from_seconds(0.25f32, TimerMode::Once)),
+                CanJump(false),
+                GroundProbe::default(),
+                Grounded::default(),
+                ExternalImpulse::default(),
+            ),
+        ))
+        .id();
+    // The stats and abilities of the skin are added once the player exists.
+    commands.add(move |world: &mut World| apply_skin(world, player, skin));
     /*
     .with_children(|child_builder| {
         let text_style = TextStyle {
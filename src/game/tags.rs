@@ -0,0 +1,228 @@
+//! Tags group level entities together, so triggers can act on all of them at once.
+//!
+//! Tags are interned, so comparing them is cheap,
+//! and the [`TagIndex`] finds the entities with a tag without scanning the world.
+
+use std::fmt;
+
+use bevy::{
+    ecs::{
+        component::{ComponentHooks, StorageType},
+        entity::EntityHashSet,
+        intern::{Interned, Interner},
+    },
+    prelude::*,
+    utils::HashMap,
+};
+use bevy_rapier2d::geometry::ColliderDisabled;
+use serde::{Deserialize, Serialize};
+
+use super::animation::SpriteAnimator;
+use crate::AppSet;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<TagIndex>();
+    app.register_type::<FadeOut>();
+    app.add_systems(
+        Update,
+        (
+            tick_fade_out.in_set(AppSet::TickTimers),
+            apply_fade_out.in_set(AppSet::Update),
+        ),
+    );
+}
+
+static TAG_INTERNER: Interner<str> = Interner::new();
+
+/// An interned name shared by a group of entities.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub struct Tag(Interned<str>);
+
+impl Tag {
+    pub fn new(name: &str) -> Self {
+        Self(TAG_INTERNER.intern(name))
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        self.0 .0
+    }
+}
+
+impl From<&str> for Tag {
+    fn from(name: &str) -> Self {
+        Self::new(name)
+    }
+}
+
+impl From<String> for Tag {
+    fn from(name: String) -> Self {
+        Self::new(&name)
+    }
+}
+
+impl From<Tag> for String {
+    fn from(tag: Tag) -> Self {
+        tag.as_str().to_string()
+    }
+}
+
+impl fmt::Debug for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Tag({:?})", self.as_str())
+    }
+}
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The tags of an entity. Inserting and removing it keeps the [`TagIndex`] up to date.
+#[derive(Debug, Clone, Default)]
+pub struct Tags(pub Vec<Tag>);
+
+impl Tags {
+    pub fn contains(&self, tag: Tag) -> bool {
+        self.0.contains(&tag)
+    }
+}
+
+impl Component for Tags {
+    const STORAGE_TYPE: StorageType = StorageType::Table;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        hooks
+            .on_insert(|mut world, entity, _| {
+                let tags = world.get::<Tags>(entity).unwrap().0.clone();
+                let mut index = world.resource_mut::<TagIndex>();
+                for tag in tags {
+                    index.0.entry(tag).or_default().insert(entity);
+                }
+            })
+            .on_remove(|mut world, entity, _| {
+                let tags = world.get::<Tags>(entity).unwrap().0.clone();
+                let mut index = world.resource_mut::<TagIndex>();
+                for tag in tags {
+                    if let Some(entities) = index.0.get_mut(&tag) {
+                        entities.remove(&entity);
+                    }
+                }
+            });
+    }
+}
+
+/// Which entities have a given tag.
+#[derive(Resource, Debug, Default)]
+pub struct TagIndex(HashMap<Tag, EntityHashSet>);
+
+impl TagIndex {
+    /// Entities with this tag.
+    /// Replacing the [`Tags`] of an entity doesn't remove it from its previous groups,
+    /// so it is checked again here.
+    pub fn entities(&self, world: &World, tag: Tag) -> Vec<Entity> {
+        self.0
+            .get(&tag)
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(|&entity| {
+                world
+                    .get::<Tags>(entity)
+                    .is_some_and(|tags| tags.contains(tag))
+            })
+            .collect()
+    }
+}
+
+/// Something done to every entity of a group at once.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum GroupOp {
+    Despawn,
+    Hide,
+    Show,
+    /// Hide the entities and turn off their colliders.
+    Disable,
+    /// Undo a [`GroupOp::Disable`].
+    Enable,
+    /// Fade out for this many seconds, then despawn.
+    FadeOut(f32),
+    /// Play an animation clip by name.
+    PlayAnimation(String),
+}
+
+impl GroupOp {
+    /// Whether the entities are gone after this operation.
+    pub fn despawns(&self) -> bool {
+        matches!(self, GroupOp::Despawn | GroupOp::FadeOut(_))
+    }
+
+    pub fn apply(self, world: &mut World, tag: Tag) {
+        let entities = world.resource::<TagIndex>().entities(world, tag);
+        for entity in entities {
+            let Some(mut entity) = world.get_entity_mut(entity) else {
+                continue;
+            };
+            match self {
+                GroupOp::Despawn => entity.despawn_recursive(),
+                GroupOp::Hide => {
+                    entity.insert(Visibility::Hidden);
+                }
+                GroupOp::Show => {
+                    entity.insert(Visibility::Inherited);
+                }
+                GroupOp::Disable => {
+                    entity.insert((Visibility::Hidden, ColliderDisabled));
+                }
+                GroupOp::Enable => {
+                    entity.insert(Visibility::Inherited);
+                    entity.remove::<ColliderDisabled>();
+                }
+                GroupOp::FadeOut(seconds) => {
+                    entity.insert((
+                        FadeOut(Timer::from_seconds(seconds, TimerMode::Once)),
+                        ColliderDisabled,
+                    ));
+                }
+                GroupOp::PlayAnimation(ref clip) => {
+                    if let Some(mut animator) = entity.get_mut::<SpriteAnimator>() {
+                        animator.play(clip);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Fades the sprite or text of its entity out, then despawns it.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub struct FadeOut(pub Timer);
+
+fn tick_fade_out(time: Res<Time>, mut query: Query<&mut FadeOut>) {
+    for mut fade_out in &mut query {
+        fade_out.0.tick(time.delta());
+    }
+}
+
+fn apply_fade_out(
+    mut commands: Commands,
+    mut query: Query<(Entity, &FadeOut, Option<&mut Sprite>, Option<&mut Text>)>,
+) {
+    for (entity, fade_out, sprite, text) in &mut query {
+        if fade_out.0.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let alpha = fade_out.0.fraction_remaining();
+        if let Some(mut sprite) = sprite {
+            sprite.color.set_alpha(alpha);
+        }
+        if let Some(mut text) = text {
+            for section in &mut text.sections {
+                section.style.color.set_alpha(alpha);
+            }
+        }
+    }
+}
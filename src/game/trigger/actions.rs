@@ -0,0 +1,233 @@
+//! Reactions to triggers, authored as an ordered list of [`TriggerAction`]s.
+//!
+//! All triggers are handled by a single dispatcher,
+//! so adding a new kind of reaction only means adding a variant to [`TriggerAction`].
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{OnTriggerEvent, OnTriggerExitEvent};
+use crate::{
+    game::{
+        assets::{level::LevelEnd, ImageKey, SfxKey},
+        audio::sfx::PlaySfx,
+        checkpoint::RespawnPoint,
+        cutscene::{play_cutscene, LevelCutscenes},
+        dialogue::start_dialogue,
+        progress::{CurrentLevel, LevelProgress, StoryFlags},
+        skin::{apply_skin, Skin},
+        spawn::{chunk::apply_group_op, level::LevelEntity, npc::SpawnNpc},
+        tags::{GroupOp, Tag, TagIndex},
+    },
+    screen::Screen,
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.observe(dispatch_trigger_actions);
+    app.observe(dispatch_trigger_exit_actions);
+    app.register_type::<DespawnTimer>();
+    app.add_systems(
+        Update,
+        (
+            tick_despawn_timers.in_set(AppSet::TickTimers),
+            despawn_expired.in_set(AppSet::Update),
+        ),
+    );
+}
+
+/// Applied to [`OnTrigger`](super::OnTrigger), these actions run in order
+/// when an entity enters the trigger.
+#[derive(Component, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TriggerActions(pub Vec<TriggerAction>);
+
+/// Like [`TriggerActions`], but run when the entity leaves the trigger.
+#[derive(Component, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TriggerExitActions(pub Vec<TriggerAction>);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TriggerAction {
+    /// Change the skin of the entity entering the trigger, with its stats and abilities.
+    ApplySkin(ImageKey),
+    /// Apply an operation to all entities with this tag.
+    Group(Tag, GroupOp),
+    /// Go to another screen.
+    ChangeScreen(Screen),
+    PlaySfx(SfxKey),
+    SpawnNpc(SpawnNpc),
+    /// Remember that something happened during this run.
+    SetStoryFlag(String),
+    /// Show a line of text above the trigger for a while.
+    ShowDialogue {
+        text: String,
+        #[serde(default = "TriggerAction::default_dialogue_duration")]
+        duration: f32,
+    },
+    /// Say lines in a speech bubble above the first character with the `speaker` tag,
+    /// or above the entity entering the trigger if there is no speaker.
+    Say {
+        #[serde(default)]
+        speaker: Option<Tag>,
+        lines: Vec<String>,
+    },
+    /// Play one of the cutscenes of the level.
+    PlayCutscene(String),
+    /// Respawn the entering player here after they fall out of the world,
+    /// with the skin they have when this action runs.
+    Checkpoint,
+    /// Complete the current level.
+    EndLevel(LevelEnd),
+}
+
+impl TriggerAction {
+    fn default_dialogue_duration() -> f32 {
+        3.0
+    }
+
+    /// Run this action for a `trigger` entered by `other`.
+    fn run(self, world: &mut World, trigger: Entity, other: Entity) {
+        match self {
+            TriggerAction::ApplySkin(key) => apply_skin(world, other, key),
+            TriggerAction::Group(tag, op) => apply_group_op(world, tag, op),
+            TriggerAction::ChangeScreen(screen) => {
+                world.resource_mut::<NextState<Screen>>().set(screen);
+            }
+            TriggerAction::PlaySfx(key) => world.trigger(PlaySfx::Key(key)),
+            TriggerAction::SpawnNpc(npc) => world.trigger(npc),
+            TriggerAction::SetStoryFlag(flag) => world.resource_mut::<StoryFlags>().set(flag),
+            TriggerAction::ShowDialogue { text, duration } => {
+                let Some(position) = world.get::<Transform>(trigger).map(|t| t.translation) else {
+                    return;
+                };
+                spawn_floating_text(world, text, position.xy() + Vec2::Y * 160.0, duration);
+            }
+            TriggerAction::Say { speaker, lines } => {
+                let speaker = match speaker {
+                    Some(tag) => world
+                        .resource::<TagIndex>()
+                        .entities(world, tag)
+                        .into_iter()
+                        .find(|&entity| world.get::<Sprite>(entity).is_some()),
+                    None => Some(other),
+                };
+                match speaker {
+                    Some(speaker) => start_dialogue(world, speaker, lines),
+                    None => warn!("No character to say {lines:?}."),
+                }
+            }
+            TriggerAction::PlayCutscene(name) => {
+                let cutscene = world
+                    .query::<&LevelCutscenes>()
+                    .iter(world)
+                    .find_map(|cutscenes| cutscenes.0.get(&name).cloned());
+                match cutscene {
+                    Some(cutscene) => play_cutscene(world, cutscene),
+                    None => warn!("No cutscene named {name:?} in this level."),
+                }
+            }
+            TriggerAction::Checkpoint => {
+                let Some(position) = world.get::<Transform>(trigger).map(|t| t.translation.xy())
+                else {
+                    return;
+                };
+                let Some(&Skin(skin)) = world.get::<Skin>(other) else {
+                    return;
+                };
+                if let Some(mut respawn_point) = world.get_mut::<RespawnPoint>(other) {
+                    *respawn_point = RespawnPoint { position, skin };
+                }
+            }
+            TriggerAction::EndLevel(end) => {
+                let current_level = world.resource::<CurrentLevel>().0;
+                world
+                    .resource_mut::<LevelProgress>()
+                    .complete(current_level);
+                let next_screen = match (end, current_level.next()) {
+                    (LevelEnd::NextLevel, Some(next)) => {
+                        world.resource_mut::<CurrentLevel>().0 = next;
+                        Screen::LevelComplete
+                    }
+                    (LevelEnd::NextLevel, None) => Screen::LevelSelect,
+                    (LevelEnd::GameOver, _) => Screen::LevelSelect,
+                };
+                world.resource_mut::<NextState<Screen>>().set(next_screen);
+            }
+        }
+    }
+}
+
+fn dispatch_trigger_actions(
+    trigger: Trigger<OnTriggerEvent>,
+    q_actions: Query<&TriggerActions>,
+    mut commands: Commands,
+) {
+    let OnTriggerEvent { trigger, other } = trigger.event().clone();
+    if let Ok(actions) = q_actions.get(trigger) {
+        run_actions(&mut commands, actions.0.clone(), trigger, other);
+    }
+}
+
+fn dispatch_trigger_exit_actions(
+    trigger: Trigger<OnTriggerExitEvent>,
+    q_actions: Query<&TriggerExitActions>,
+    mut commands: Commands,
+) {
+    let OnTriggerExitEvent { trigger, other } = trigger.event().clone();
+    if let Ok(actions) = q_actions.get(trigger) {
+        run_actions(&mut commands, actions.0.clone(), trigger, other);
+    }
+}
+
+fn run_actions(
+    commands: &mut Commands,
+    actions: Vec<TriggerAction>,
+    trigger: Entity,
+    other: Entity,
+) {
+    commands.add(move |world: &mut World| {
+        for action in actions {
+            action.run(world, trigger, other);
+        }
+    });
+}
+
+/// Show a line of text in the world for a while.
+pub fn spawn_floating_text(world: &mut World, text: String, position: Vec2, duration: f32) {
+    world.spawn((
+        Name::new("Dialogue"),
+        Text2dBundle {
+            text: Text::from_section(
+                text,
+                TextStyle {
+                    font_size: 30.0,
+                    ..default()
+                },
+            )
+            .with_justify(JustifyText::Center),
+            transform: Transform::from_translation(position.extend(0.0)),
+            ..default()
+        },
+        DespawnTimer(Timer::from_seconds(duration, TimerMode::Once)),
+        LevelEntity,
+        StateScoped(Screen::Playing),
+    ));
+}
+
+/// Despawns its entity when the timer finishes.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub struct DespawnTimer(pub Timer);
+
+fn tick_despawn_timers(time: Res<Time>, mut query: Query<&mut DespawnTimer>) {
+    for mut timer in &mut query {
+        timer.0.tick(time.delta());
+    }
+}
+
+fn despawn_expired(mut commands: Commands, query: Query<(Entity, &DespawnTimer)>) {
+    for (entity, timer) in &query {
+        if timer.0.finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
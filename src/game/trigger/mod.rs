@@ -1,45 +1,223 @@
 //! Handles rapier triggers and reactions
 
-use bevy::prelude::*;
+use bevy::{
+    ecs::component::{ComponentHooks, StorageType},
+    prelude::*,
+    utils::HashSet,
+};
 use bevy_rapier2d::pipeline::CollisionEvent;
+use serde::{Deserialize, Serialize};
+
+use super::{progress::StoryFlags, spawn::player::Player};
+use crate::AppSet;
+
+pub mod actions;
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_systems(Update, trigger_react);
+    app.add_plugins(actions::plugin);
+    app.add_systems(
+        Update,
+        (
+            tick_trigger_cooldowns.in_set(AppSet::TickTimers),
+            trigger_react,
+        ),
+    );
+}
+
+/// A sensor sending [`OnTriggerEvent`] and [`OnTriggerExitEvent`]
+/// when entities matching its [`TriggerFilter`] enter and leave it.
+#[derive(Debug, Clone, Default)]
+pub struct OnTrigger {
+    pub policy: TriggerPolicy,
+    pub filter: TriggerFilter,
+    /// Entries are ignored until this [`StoryFlags`] flag is set.
+    pub requires_flag: Option<String>,
+}
+
+impl Component for OnTrigger {
+    const STORAGE_TYPE: StorageType = StorageType::Table;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        hooks.on_add(|mut world, entity, _| {
+            // A state may be restored along with the trigger, see `LevelStream`.
+            if world.get::<TriggerState>(entity).is_none() {
+                world
+                    .commands()
+                    .entity(entity)
+                    .insert(TriggerState::default());
+            }
+        });
+    }
+}
+
+/// When a trigger fires for an entity entering it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum TriggerPolicy {
+    /// Only the first entry fires.
+    Once,
+    #[default]
+    EveryEntry,
+    /// Entries are ignored for this many seconds after the trigger fired.
+    Cooldown(f32),
+}
+
+/// Which entities a trigger reacts to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TriggerFilter {
+    Any,
+    #[default]
+    Player,
+}
+
+/// Runtime state of an [`OnTrigger`], added along with it.
+#[derive(Component, Debug, Clone, Default)]
+pub struct TriggerState {
+    fired: bool,
+    cooldown: Option<Timer>,
+    /// Entities the trigger fired for that haven't left yet.
+    inside: HashSet<Entity>,
 }
 
-#[derive(Component)]
-pub struct OnTrigger;
+impl TriggerState {
+    fn can_fire(&self, policy: TriggerPolicy) -> bool {
+        match (policy, &self.cooldown) {
+            (TriggerPolicy::Once, _) => !self.fired,
+            (TriggerPolicy::EveryEntry, _) | (TriggerPolicy::Cooldown(_), None) => true,
+            (TriggerPolicy::Cooldown(_), Some(cooldown)) => cooldown.finished(),
+        }
+    }
+
+    /// The state to give the trigger when it is spawned again after being despawned.
+    /// Nothing is inside a new sensor.
+    pub fn detached(&self) -> Self {
+        Self {
+            inside: default(),
+            ..self.clone()
+        }
+    }
+
+    fn fire(&mut self, policy: TriggerPolicy, other: Entity) {
+        self.fired = true;
+        if let TriggerPolicy::Cooldown(seconds) = policy {
+            self.cooldown = Some(Timer::from_seconds(seconds, TimerMode::Once));
+        }
+        self.inside.insert(other);
+    }
+}
 
+/// Sent when an entity enters a trigger, according to its [`TriggerPolicy`].
 #[derive(Clone, Debug, Event)]
 pub struct OnTriggerEvent {
     pub trigger: Entity,
     pub other: Entity,
 }
 
+/// Sent when an entity leaves a trigger that fired an [`OnTriggerEvent`] for it.
+#[derive(Clone, Debug, Event)]
+pub struct OnTriggerExitEvent {
+    pub trigger: Entity,
+    pub other: Entity,
+}
+
+fn tick_trigger_cooldowns(time: Res<Time>, mut query: Query<&mut TriggerState>) {
+    for mut state in &mut query {
+        if let Some(cooldown) = &mut state.cooldown {
+            cooldown.tick(time.delta());
+        }
+    }
+}
+
 pub fn trigger_react(
     mut commands: Commands,
-    q_on_trigger: Query<&OnTrigger>,
+    mut q_on_trigger: Query<(&OnTrigger, &mut TriggerState)>,
+    q_player: Query<(), With<Player>>,
+    story_flags: Res<StoryFlags>,
     mut collision_events: EventReader<CollisionEvent>,
 ) {
     for collision_event in collision_events.read() {
-        match collision_event {
-            CollisionEvent::Started(e1, e2, _flags) => {
-                if let Ok(_on_trigger) = q_on_trigger.get(*e1) {
-                    dbg!("sending for e1");
-                    commands.trigger(OnTriggerEvent {
-                        trigger: *e1,
-                        other: *e2,
-                    })
-                }
-                if let Ok(_on_trigger) = q_on_trigger.get(*e2) {
-                    dbg!("sending for e2");
-                    commands.trigger(OnTriggerEvent {
-                        trigger: *e2,
-                        other: *e1,
-                    });
+        let (e1, e2, started) = match collision_event {
+            CollisionEvent::Started(e1, e2, _flags) => (*e1, *e2, true),
+            CollisionEvent::Stopped(e1, e2, _flags) => (*e1, *e2, false),
+        };
+        for (trigger, other) in [(e1, e2), (e2, e1)] {
+            let Ok((on_trigger, mut state)) = q_on_trigger.get_mut(trigger) else {
+                continue;
+            };
+            if !started {
+                // The other entity may already be despawned, so it isn't filtered again.
+                if state.inside.remove(&other) {
+                    commands.trigger(OnTriggerExitEvent { trigger, other });
                 }
+                continue;
+            }
+            let accepted = match on_trigger.filter {
+                TriggerFilter::Any => true,
+                TriggerFilter::Player => q_player.contains(other),
+            } && on_trigger
+                .requires_flag
+                .as_deref()
+                .is_none_or(|flag| story_flags.is_set(flag));
+            if accepted && state.can_fire(on_trigger.policy) {
+                state.fire(on_trigger.policy, other);
+                commands.trigger(OnTriggerEvent { trigger, other });
             }
-            CollisionEvent::Stopped(_, _, _) => {}
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy_rapier2d::rapier::geometry::CollisionEventFlags;
+
+    use super::*;
+
+    #[derive(Resource, Default)]
+    struct Fired(usize);
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_event::<CollisionEvent>();
+        app.init_resource::<StoryFlags>();
+        app.init_resource::<Fired>();
+        app.add_systems(Update, trigger_react);
+        app.observe(
+            |_trigger: Trigger<OnTriggerEvent>, mut fired: ResMut<Fired>| {
+                fired.0 += 1;
+            },
+        );
+        app
+    }
+
+    fn enter(app: &mut App, trigger: Entity, other: Entity) {
+        app.world_mut().send_event(CollisionEvent::Started(
+            trigger,
+            other,
+            CollisionEventFlags::SENSOR,
+        ));
+        app.update();
+    }
+
+    #[test]
+    fn trigger_waits_for_its_story_flag() {
+        let mut app = app();
+        let trigger = app
+            .world_mut()
+            .spawn(OnTrigger {
+                policy: TriggerPolicy::Once,
+                filter: TriggerFilter::Any,
+                requires_flag: Some("met the dev".to_string()),
+            })
+            .id();
+        let other = app.world_mut().spawn_empty().id();
+
+        enter(&mut app, trigger, other);
+        assert_eq!(app.world().resource::<Fired>().0, 0);
+
+        app.world_mut()
+            .resource_mut::<StoryFlags>()
+            .set("met the dev");
+        enter(&mut app, trigger, other);
+        assert_eq!(app.world().resource::<Fired>().0, 1);
+    }
+}
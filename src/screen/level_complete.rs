@@ -0,0 +1,69 @@
+//! A screen shown between two levels.
+
+use bevy::prelude::*;
+
+use super::Screen;
+use crate::{
+    game::{
+        assets::{level::Level, HandleMap, LevelKey},
+        progress::CurrentLevel,
+    },
+    ui::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::LevelComplete), enter_level_complete);
+
+    app.add_systems(
+        Update,
+        handle_level_complete_action.run_if(in_state(Screen::LevelComplete)),
+    );
+    app.register_type::<LevelCompleteAction>();
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum LevelCompleteAction {
+    Continue,
+    LevelSelect,
+}
+
+fn enter_level_complete(
+    mut commands: Commands,
+    current_level: Res<CurrentLevel>,
+    level_handles: Res<HandleMap<LevelKey>>,
+    levels: Res<Assets<Level>>,
+) {
+    let next_name = levels.get(&level_handles[&current_level.0]).map_or_else(
+        || format!("{:?}", current_level.0),
+        |level| level.name.clone(),
+    );
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::LevelComplete))
+        .with_children(|children| {
+            children.header("Level complete!");
+            children.label(format!("Next up: {next_name}"));
+
+            children
+                .button("Continue")
+                .insert(LevelCompleteAction::Continue);
+            children
+                .button("Levels")
+                .insert(LevelCompleteAction::LevelSelect);
+        });
+}
+
+fn handle_level_complete_action(
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut button_query: InteractionQuery<&LevelCompleteAction>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match action {
+                LevelCompleteAction::Continue => next_screen.set(Screen::Playing),
+                LevelCompleteAction::LevelSelect => next_screen.set(Screen::LevelSelect),
+            }
+        }
+    }
+}
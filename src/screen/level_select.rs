@@ -0,0 +1,73 @@
+//! A level select screen that can be accessed from the title screen.
+
+use bevy::prelude::*;
+
+use super::Screen;
+use crate::{
+    game::{
+        assets::{level::Level, HandleMap, LevelKey},
+        progress::{CurrentLevel, LevelProgress},
+    },
+    ui::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::LevelSelect), enter_level_select);
+
+    app.add_systems(
+        Update,
+        handle_level_select_action.run_if(in_state(Screen::LevelSelect)),
+    );
+    app.register_type::<LevelSelectAction>();
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum LevelSelectAction {
+    Play(LevelKey),
+    Back,
+}
+
+fn enter_level_select(
+    mut commands: Commands,
+    progress: Res<LevelProgress>,
+    level_handles: Res<HandleMap<LevelKey>>,
+    levels: Res<Assets<Level>>,
+) {
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::LevelSelect))
+        .with_children(|children| {
+            children.header("Levels");
+            for key in LevelKey::ALL {
+                let name = levels
+                    .get(&level_handles[&key])
+                    .map_or_else(|| format!("{key:?}"), |level| level.name.clone());
+                if progress.is_unlocked(key) {
+                    children.button(name).insert(LevelSelectAction::Play(key));
+                } else {
+                    children.label(format!("{name} (locked)"));
+                }
+            }
+
+            children.button("Back").insert(LevelSelectAction::Back);
+        });
+}
+
+fn handle_level_select_action(
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut current_level: ResMut<CurrentLevel>,
+    mut button_query: InteractionQuery<&LevelSelectAction>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match action {
+                LevelSelectAction::Play(key) => {
+                    current_level.0 = *key;
+                    next_screen.set(Screen::Playing);
+                }
+                LevelSelectAction::Back => next_screen.set(Screen::Title),
+            }
+        }
+    }
+}
@@ -1,33 +1,55 @@
 //! The game's main screen states and transitions between them.
 
 mod credits;
+mod level_complete;
+mod level_select;
 mod loading;
 mod playing;
+mod settings;
 mod splash;
 mod title;
 
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 pub(super) fn plugin(app: &mut App) {
     app.init_state::<Screen>();
     app.enable_state_scoped_entities::<Screen>();
+    app.add_sub_state::<PlayingState>();
 
     app.add_plugins((
         splash::plugin,
         loading::plugin,
         title::plugin,
         credits::plugin,
+        level_select::plugin,
         playing::plugin,
+        level_complete::plugin,
+        settings::plugin,
     ));
 }
 
 /// The game's main screen states.
-#[derive(States, Debug, Hash, PartialEq, Eq, Clone, Default)]
+#[derive(States, Debug, Hash, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
 pub enum Screen {
     #[default]
     Splash,
     Loading,
     Title,
     Credits,
+    Settings,
+    LevelSelect,
     Playing,
+    LevelComplete,
+}
+
+/// Who is in control while [`Screen::Playing`].
+#[derive(SubStates, Debug, Hash, PartialEq, Eq, Clone, Default)]
+#[source(Screen = Screen::Playing)]
+pub enum PlayingState {
+    /// The player controls their character.
+    #[default]
+    Gameplay,
+    /// A cutscene is running and player input is ignored.
+    Cutscene,
 }
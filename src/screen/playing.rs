@@ -1,10 +1,14 @@
 //! The screen state for the main game loop.
 
-use bevy::{input::common_conditions::input_just_pressed, prelude::*};
+use bevy::prelude::*;
 
 use super::Screen;
 use crate::game::{
-    assets::SoundtrackKey, audio::soundtrack::PlaySoundtrack, spawn::level::SpawnLevel,
+    assets::{HandleMap, LevelKey, SoundtrackKey},
+    audio::soundtrack::PlaySoundtrack,
+    input::{action_just_pressed, InputAction},
+    progress::CurrentLevel,
+    spawn::level::SpawnLevel,
 };
 
 pub(super) fn plugin(app: &mut App) {
@@ -14,12 +18,16 @@ pub(super) fn plugin(app: &mut App) {
     app.add_systems(
         Update,
         return_to_title_screen
-            .run_if(in_state(Screen::Playing).and_then(input_just_pressed(KeyCode::Escape))),
+            .run_if(in_state(Screen::Playing).and_then(action_just_pressed(InputAction::Pause))),
     );
 }
 
-fn enter_playing(mut commands: Commands) {
-    commands.trigger(SpawnLevel);
+fn enter_playing(
+    mut commands: Commands,
+    current_level: Res<CurrentLevel>,
+    level_handles: Res<HandleMap<LevelKey>>,
+) {
+    commands.trigger(SpawnLevel(level_handles[&current_level.0].clone_weak()));
     commands.trigger(PlaySoundtrack::Key(SoundtrackKey::Gameplay));
 }
 
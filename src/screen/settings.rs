@@ -0,0 +1,66 @@
+//! A settings screen that can be accessed from the title screen.
+
+use bevy::prelude::*;
+
+use super::Screen;
+use crate::{
+    game::movement::{RunMode, RunModeSetting},
+    ui::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Settings), enter_settings);
+
+    app.add_systems(
+        Update,
+        handle_settings_action.run_if(in_state(Screen::Settings)),
+    );
+    app.register_type::<SettingsAction>();
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum SettingsAction {
+    CycleRunMode,
+    Back,
+}
+
+fn run_mode_text(run_mode: RunMode) -> String {
+    format!("Run: {}", run_mode.label())
+}
+
+fn enter_settings(mut commands: Commands, run_mode: Res<RunModeSetting>) {
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::Settings))
+        .with_children(|children| {
+            children.header("Settings");
+            children.label("Some levels always use the same run mode.");
+            children
+                .button(run_mode_text(run_mode.0))
+                .insert(SettingsAction::CycleRunMode);
+            children.button("Back").insert(SettingsAction::Back);
+        });
+}
+
+fn handle_settings_action(
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut run_mode: ResMut<RunModeSetting>,
+    mut button_query: InteractionQuery<(&SettingsAction, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    for (interaction, (action, children)) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match action {
+                SettingsAction::CycleRunMode => {
+                    run_mode.0 = run_mode.0.next();
+                    let mut texts = text_query.iter_many_mut(children);
+                    while let Some(mut text) = texts.fetch_next() {
+                        text.sections[0].value = run_mode_text(run_mode.0);
+                    }
+                }
+                SettingsAction::Back => next_screen.set(Screen::Title),
+            }
+        }
+    }
+}
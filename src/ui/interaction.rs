@@ -1,16 +1,25 @@
 use bevy::prelude::*;
 
+use super::navigation::Focused;
 use crate::game::{assets::SfxKey, audio::sfx::PlaySfx};
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<InteractionPalette>();
-    app.add_systems(Update, (apply_interaction_palette, trigger_interaction_sfx));
+    app.add_systems(
+        Update,
+        (
+            apply_interaction_palette,
+            trigger_interaction_sfx,
+            trigger_focus_sfx,
+        ),
+    );
 }
 
 pub type InteractionQuery<'w, 's, T> =
     Query<'w, 's, (&'static Interaction, T), Changed<Interaction>>;
 
 /// Palette for widget interactions.
+/// A [`Focused`] widget uses the hovered color.
 #[derive(Component, Debug, Reflect)]
 #[reflect(Component)]
 pub struct InteractionPalette {
@@ -20,15 +29,21 @@ pub struct InteractionPalette {
 }
 
 fn apply_interaction_palette(
-    mut palette_query: InteractionQuery<(&InteractionPalette, &mut BackgroundColor)>,
+    mut palette_query: Query<(
+        &Interaction,
+        Has<Focused>,
+        &InteractionPalette,
+        &mut BackgroundColor,
+    )>,
 ) {
-    for (interaction, (palette, mut background)) in &mut palette_query {
-        *background = match interaction {
-            Interaction::None => palette.none,
-            Interaction::Hovered => palette.hovered,
-            Interaction::Pressed => palette.pressed,
-        }
-        .into();
+    // Losing focus isn't a change we can filter on, so check every widget.
+    for (interaction, focused, palette, mut background) in &mut palette_query {
+        let color = match (interaction, focused) {
+            (Interaction::Pressed, _) => palette.pressed,
+            (Interaction::Hovered, _) | (Interaction::None, true) => palette.hovered,
+            (Interaction::None, false) => palette.none,
+        };
+        background.set_if_neq(color.into());
     }
 }
 
@@ -44,3 +59,9 @@ fn trigger_interaction_sfx(
         }
     }
 }
+
+fn trigger_focus_sfx(focused: Query<(), Added<Focused>>, mut commands: Commands) {
+    for _ in &focused {
+        commands.trigger(PlaySfx::Key(SfxKey::ButtonHover));
+    }
+}
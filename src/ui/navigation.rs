@@ -0,0 +1,86 @@
+//! Navigate between buttons with the keyboard or a gamepad, and press them without a pointer.
+
+use bevy::{prelude::*, ui::UiSystem};
+
+use crate::game::input::{record_actions, ActionState, InputAction};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Focusable>();
+    app.register_type::<Focused>();
+    app.add_systems(
+        PreUpdate,
+        navigate_buttons
+            .after(UiSystem::Focus)
+            .after(record_actions),
+    );
+}
+
+/// A button that can get the focus. Added by [`Widgets::button`](super::widgets::Widgets::button).
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct Focusable;
+
+/// The button that [`InputAction::MenuConfirm`] presses.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub struct Focused;
+
+/// Move the focus between buttons from top to bottom, and press the focused one.
+/// Pressing sets [`Interaction`], so buttons are handled the same as with a mouse.
+fn navigate_buttons(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    mut q_buttons: Query<
+        (Entity, &GlobalTransform, &mut Interaction, Has<Focused>),
+        With<Focusable>,
+    >,
+    mut pressed: Local<Option<Entity>>,
+) {
+    // Release the button pressed last frame, as a mouse button would be.
+    if let Some(entity) = pressed.take() {
+        if let Ok((_, _, mut interaction, _)) = q_buttons.get_mut(entity) {
+            interaction.set_if_neq(Interaction::None);
+        }
+    }
+
+    let mut buttons: Vec<(Entity, Vec3, bool)> = q_buttons
+        .iter()
+        .map(|(entity, transform, _, focused)| (entity, transform.translation(), focused))
+        .collect();
+    if buttons.is_empty() {
+        return;
+    }
+    // UI coordinates go down, so this is the layout order of a column of buttons.
+    buttons.sort_by(|(_, a, _), (_, b, _)| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+    let focused = buttons.iter().position(|(_, _, focused)| *focused);
+
+    let step = match (
+        actions.just_pressed(InputAction::MenuUp),
+        actions.just_pressed(InputAction::MenuDown),
+    ) {
+        (true, false) => Some(buttons.len() - 1),
+        (false, true) => Some(1),
+        _ => None,
+    };
+    if let Some(step) = step {
+        let next = match focused {
+            Some(focused) => (focused + step) % buttons.len(),
+            None => 0,
+        };
+        if let Some(focused) = focused {
+            commands.entity(buttons[focused].0).remove::<Focused>();
+        }
+        commands.entity(buttons[next].0).insert(Focused);
+        return;
+    }
+
+    if let Some(focused) = focused {
+        if actions.just_pressed(InputAction::MenuConfirm) {
+            let entity = buttons[focused].0;
+            if let Ok((_, _, mut interaction, _)) = q_buttons.get_mut(entity) {
+                *interaction = Interaction::Pressed;
+                *pressed = Some(entity);
+            }
+        }
+    }
+}